@@ -0,0 +1,214 @@
+//
+// Copyright 2023, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// If set, the signing key is read from this path instead of being generated
+const PRIV_KEY_PATH_ENV: &str = "BANSCII_ARTIST_PRIV_KEY_PATH";
+
+// If set, the public key is also written, in PEM form, to this path
+const PUB_KEY_OUT_PATH_ENV: &str = "BANSCII_ARTIST_PUB_KEY_OUT_PATH";
+
+#[cfg(not(any(
+    feature = "scheme-rsa",
+    feature = "scheme-ed25519",
+    feature = "scheme-ecdsa-p256"
+)))]
+compile_error!("one of the scheme-* features must be enabled");
+
+#[cfg(any(
+    all(feature = "scheme-rsa", feature = "scheme-ed25519"),
+    all(feature = "scheme-rsa", feature = "scheme-ecdsa-p256"),
+    all(feature = "scheme-ed25519", feature = "scheme-ecdsa-p256"),
+))]
+compile_error!("only one of the scheme-* features may be enabled");
+
+// So that the errors above are the only ones, where several schemes are enabled only the first is
+// used, and where none is, these stand in for its functions
+#[cfg(not(any(
+    feature = "scheme-rsa",
+    feature = "scheme-ed25519",
+    feature = "scheme-ecdsa-p256"
+)))]
+fn generate_key_pair() -> KeyPair {
+    unreachable!()
+}
+
+#[cfg(not(any(
+    feature = "scheme-rsa",
+    feature = "scheme-ed25519",
+    feature = "scheme-ecdsa-p256"
+)))]
+fn key_pair_from_pem(_pem: &str) -> Result<KeyPair, String> {
+    unreachable!()
+}
+
+struct KeyPair {
+    // In the form expected by src/cryptographic_secrets/
+    priv_key_pem: String,
+    // SubjectPublicKeyInfo
+    pub_key_der: Vec<u8>,
+    pub_key_pem: String,
+}
+
+fn main() {
+    let key_pair = match env::var_os(PRIV_KEY_PATH_ENV) {
+        Some(path) => load_key_pair(Path::new(&path)),
+        None => generate_key_pair(),
+    };
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let out_path = PathBuf::from(&out_dir).join("priv.pem");
+    fs::write(out_path, &key_pair.priv_key_pem).unwrap();
+
+    fs::write(
+        PathBuf::from(&out_dir).join("pub.der"),
+        &key_pair.pub_key_der,
+    )
+    .unwrap();
+    fs::write(
+        PathBuf::from(&out_dir).join("pub.pem"),
+        &key_pair.pub_key_pem,
+    )
+    .unwrap();
+
+    if let Some(path) = env::var_os(PUB_KEY_OUT_PATH_ENV) {
+        fs::write(path, &key_pair.pub_key_pem).unwrap();
+    }
+
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed={PRIV_KEY_PATH_ENV}");
+    println!("cargo:rerun-if-env-changed={PUB_KEY_OUT_PATH_ENV}");
+}
+
+fn load_key_pair(path: &Path) -> KeyPair {
+    println!("cargo:rerun-if-changed={}", path.display());
+    let pem = fs::read_to_string(path).unwrap_or_else(|err| {
+        panic!(
+            "{PRIV_KEY_PATH_ENV}: failed to read {}: {err}",
+            path.display()
+        )
+    });
+    key_pair_from_pem(&pem).unwrap_or_else(|err| {
+        panic!(
+            "{PRIV_KEY_PATH_ENV}: {} is not a valid signing key for this scheme: {err}",
+            path.display()
+        )
+    })
+}
+
+#[cfg(feature = "scheme-rsa")]
+const RSA_KEY_SIZE: usize = 2048;
+
+#[cfg(feature = "scheme-rsa")]
+fn generate_key_pair() -> KeyPair {
+    let priv_key = rsa::RsaPrivateKey::new(&mut rand_core::OsRng, RSA_KEY_SIZE).unwrap();
+    rsa_key_pair(&priv_key)
+}
+
+// Accepts PKCS#1 or PKCS#8 PEM
+#[cfg(feature = "scheme-rsa")]
+fn key_pair_from_pem(pem: &str) -> Result<KeyPair, String> {
+    use rsa::pkcs1::DecodeRsaPrivateKey;
+    use rsa::pkcs8::DecodePrivateKey;
+
+    let priv_key = rsa::RsaPrivateKey::from_pkcs1_pem(pem)
+        .or_else(|_| rsa::RsaPrivateKey::from_pkcs8_pem(pem))
+        .map_err(|err| err.to_string())?;
+    priv_key.validate().map_err(|err| err.to_string())?;
+    Ok(rsa_key_pair(&priv_key))
+}
+
+#[cfg(feature = "scheme-rsa")]
+fn rsa_key_pair(priv_key: &rsa::RsaPrivateKey) -> KeyPair {
+    use rsa::pkcs1::EncodeRsaPrivateKey;
+    use rsa::pkcs8::EncodePublicKey;
+
+    let pub_key = priv_key.to_public_key();
+    KeyPair {
+        priv_key_pem: priv_key
+            .to_pkcs1_pem(rsa::pkcs1::LineEnding::LF)
+            .unwrap()
+            .to_string(),
+        pub_key_der: pub_key.to_public_key_der().unwrap().into_vec(),
+        pub_key_pem: pub_key
+            .to_public_key_pem(rsa::pkcs8::LineEnding::LF)
+            .unwrap(),
+    }
+}
+
+#[cfg(all(feature = "scheme-ed25519", not(feature = "scheme-rsa")))]
+fn generate_key_pair() -> KeyPair {
+    let signing_key = ed25519_dalek::SigningKey::generate(&mut rand_core::OsRng);
+    ed25519_key_pair(&signing_key)
+}
+
+// Accepts PKCS#8 PEM
+#[cfg(all(feature = "scheme-ed25519", not(feature = "scheme-rsa")))]
+fn key_pair_from_pem(pem: &str) -> Result<KeyPair, String> {
+    use ed25519_dalek::pkcs8::DecodePrivateKey;
+
+    let signing_key =
+        ed25519_dalek::SigningKey::from_pkcs8_pem(pem).map_err(|err| err.to_string())?;
+    Ok(ed25519_key_pair(&signing_key))
+}
+
+#[cfg(all(feature = "scheme-ed25519", not(feature = "scheme-rsa")))]
+fn ed25519_key_pair(signing_key: &ed25519_dalek::SigningKey) -> KeyPair {
+    use ed25519_dalek::pkcs8::{spki::der::pem::LineEnding, EncodePrivateKey, EncodePublicKey};
+
+    let verifying_key = signing_key.verifying_key();
+    KeyPair {
+        priv_key_pem: signing_key
+            .to_pkcs8_pem(LineEnding::LF)
+            .unwrap()
+            .to_string(),
+        pub_key_der: verifying_key.to_public_key_der().unwrap().into_vec(),
+        pub_key_pem: verifying_key.to_public_key_pem(LineEnding::LF).unwrap(),
+    }
+}
+
+#[cfg(all(
+    feature = "scheme-ecdsa-p256",
+    not(any(feature = "scheme-rsa", feature = "scheme-ed25519"))
+))]
+fn generate_key_pair() -> KeyPair {
+    let signing_key = p256::ecdsa::SigningKey::random(&mut rand_core::OsRng);
+    ecdsa_p256_key_pair(&signing_key)
+}
+
+// Accepts PKCS#8 or SEC1 PEM
+#[cfg(all(
+    feature = "scheme-ecdsa-p256",
+    not(any(feature = "scheme-rsa", feature = "scheme-ed25519"))
+))]
+fn key_pair_from_pem(pem: &str) -> Result<KeyPair, String> {
+    use p256::pkcs8::DecodePrivateKey;
+
+    let signing_key = p256::ecdsa::SigningKey::from_pkcs8_pem(pem)
+        .or_else(|_| p256::SecretKey::from_sec1_pem(pem).map(Into::into))
+        .map_err(|err| err.to_string())?;
+    Ok(ecdsa_p256_key_pair(&signing_key))
+}
+
+#[cfg(all(
+    feature = "scheme-ecdsa-p256",
+    not(any(feature = "scheme-rsa", feature = "scheme-ed25519"))
+))]
+fn ecdsa_p256_key_pair(signing_key: &p256::ecdsa::SigningKey) -> KeyPair {
+    use p256::pkcs8::{EncodePrivateKey, EncodePublicKey, LineEnding};
+
+    let verifying_key = signing_key.verifying_key();
+    KeyPair {
+        priv_key_pem: signing_key
+            .to_pkcs8_pem(LineEnding::LF)
+            .unwrap()
+            .to_string(),
+        pub_key_der: verifying_key.to_public_key_der().unwrap().into_vec(),
+        pub_key_pem: verifying_key.to_public_key_pem(LineEnding::LF).unwrap(),
+    }
+}
@@ -0,0 +1,160 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+// Feeds arbitrary sequences of requests and region contents through the artist. Besides never
+// panicking, every masterpiece the artist completes must carry a signature that the artist itself
+// accepts.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+
+use banscii_artist_core::{Artist, MemoryStorage};
+use banscii_artist_interface_types::*;
+
+const REGION_SIZE: usize = 0x4_000;
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    steps: Vec<Step>,
+    now_ms: u64,
+}
+
+#[derive(Debug, Arbitrary)]
+struct Step {
+    request: Vec<u8>,
+    region_in: Vec<u8>,
+}
+
+// What the artist has returned so far for the completion in progress
+struct Completion {
+    height: usize,
+    width: usize,
+    subject: Vec<u8>,
+    edition: u64,
+    color_mode: ColorMode,
+    // As returned by each chunk
+    chunks: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+fuzz_target!(|input: Input| {
+    let mut artist = Artist::new(MemoryStorage::new()).unwrap();
+    let mut completion = None;
+
+    for step in input.steps {
+        let Ok(req) = postcard::from_bytes::<Request>(&step.request) else {
+            continue;
+        };
+
+        let mut region_in = step.region_in;
+        region_in.resize(REGION_SIZE, 0);
+        let mut region_out = vec![0; REGION_SIZE];
+
+        let begun = match &req {
+            Request::BeginComplete(req) => Some((
+                subject(&region_in, req.subject_start, req.subject_size),
+                req.color_mode,
+            )),
+            _ => None,
+        };
+        let leaves_transfer = matches!(req, Request::GetPublicKey | Request::GetStatus);
+        let in_progress = completion.take();
+
+        match artist.handle_request(req, input.now_ms, &region_in, &mut region_out) {
+            Ok(Response::BeginComplete(resp)) => {
+                let (subject, color_mode) = begun.unwrap();
+                completion = Some(Completion {
+                    height: resp.height,
+                    width: resp.width,
+                    subject: subject.unwrap(),
+                    edition: resp.edition,
+                    color_mode,
+                    chunks: Vec::new(),
+                });
+            }
+            Ok(Response::CompleteChunk(resp)) => {
+                let mut in_progress = in_progress.unwrap();
+                in_progress.chunks.push((
+                    region_out[resp.masterpiece_start..][..resp.masterpiece_size].to_vec(),
+                    region_out[resp.colors_start..][..resp.colors_size].to_vec(),
+                ));
+                completion = Some(in_progress);
+            }
+            Ok(Response::FinishComplete(resp)) => {
+                let signature = &region_out[resp.signature_start..][..resp.signature_size];
+                check_signature(
+                    &mut artist,
+                    in_progress.unwrap(),
+                    signature,
+                    resp.signature_algorithm,
+                );
+            }
+            _ if leaves_transfer => {
+                completion = in_progress;
+            }
+            // Anything else that is part of a completion or verification abandons the completion
+            // in progress, as does any error
+            _ => {}
+        }
+    }
+});
+
+fn subject(region_in: &[u8], start: usize, size: usize) -> Option<Vec<u8>> {
+    let end = start.checked_add(size)?;
+    region_in.get(start..end).map(<[u8]>::to_vec)
+}
+
+fn check_signature(
+    artist: &mut Artist<MemoryStorage>,
+    completion: Completion,
+    signature: &[u8],
+    signature_algorithm: SignatureAlgorithm,
+) {
+    let mut region_in = completion.subject.clone();
+    region_in.extend_from_slice(signature);
+    region_in.resize(REGION_SIZE, 0);
+
+    let req = Request::BeginVerify(BeginVerifyRequest {
+        height: completion.height,
+        width: completion.width,
+        subject_start: 0,
+        subject_size: completion.subject.len(),
+        edition: completion.edition,
+        color_mode: completion.color_mode,
+        signature_start: completion.subject.len(),
+        signature_size: signature.len(),
+        signature_algorithm,
+    });
+    call(artist, req, region_in);
+
+    // The masterpiece is sent back in the chunks in which it was returned
+    for (pixel_data, colors) in completion.chunks {
+        let mut region_in = pixel_data.clone();
+        region_in.extend_from_slice(&colors);
+        region_in.resize(REGION_SIZE, 0);
+
+        let req = Request::VerifyChunk(VerifyChunkRequest {
+            masterpiece_start: 0,
+            masterpiece_size: pixel_data.len(),
+            colors_start: pixel_data.len(),
+            colors_size: colors.len(),
+        });
+        call(artist, req, region_in);
+    }
+
+    match call(artist, Request::FinishVerify, vec![0; REGION_SIZE]) {
+        Response::FinishVerify(VerifyResponse { valid: true }) => {}
+        resp => panic!("signature of completed masterpiece not accepted: {resp:?}"),
+    }
+}
+
+fn call(artist: &mut Artist<MemoryStorage>, req: Request, region_in: Vec<u8>) -> Response {
+    match artist.handle_request(req, 0, &region_in, &mut vec![0; REGION_SIZE]) {
+        Ok(resp) => resp,
+        Err(err) => panic!("verification of completed masterpiece failed: {err:?}"),
+    }
+}
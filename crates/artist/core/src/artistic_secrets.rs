@@ -0,0 +1,317 @@
+//
+// Copyright 2023, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem;
+
+use banscii_artist_interface_types::{BuiltInPalette, ColorMode, Dither, RenderMode};
+
+// A masterpiece in the making. Its draft arrives a few rows at a time, and each band of rows is
+// completed as soon as it arrives, so that neither need be held in full.
+pub(crate) struct Masterpiece {
+    pub(crate) height: usize,
+    pub(crate) width: usize,
+    pub(crate) color_mode: ColorMode,
+    draft_height: usize,
+    draft_width: usize,
+    palette: Vec<char>,
+    dither: Dither,
+    render_mode: RenderMode,
+    // The most bytes that a cell's character and colours can take
+    max_cell_size: usize,
+    // Draft rows completed so far
+    draft_rows_done: usize,
+    // Floyd-Steinberg errors carried to the next row, offset by one so that there is room on either
+    // side
+    errors: Vec<i32>,
+}
+
+impl Masterpiece {
+    // The draft must not be empty, and `palette` must not be empty either
+    pub(crate) fn new(
+        draft_height: usize,
+        draft_width: usize,
+        palette: Vec<char>,
+        dither: Dither,
+        render_mode: RenderMode,
+        color_mode: ColorMode,
+    ) -> Self {
+        let max_char_size = match render_mode {
+            RenderMode::Palette => palette.iter().map(|c| c.len_utf8()).max().unwrap(),
+            RenderMode::Quadrants | RenderMode::Braille => BLOCK_CHAR_SIZE,
+        };
+        Self {
+            height: draft_height.div_ceil(render_mode.cell_height()),
+            width: draft_width.div_ceil(render_mode.cell_width()),
+            color_mode,
+            draft_height,
+            draft_width,
+            palette,
+            dither,
+            render_mode,
+            max_cell_size: max_char_size + color_mode.cell_size(),
+            draft_rows_done: 0,
+            errors: vec![0; draft_width + 2],
+        }
+    }
+
+    pub(crate) fn draft_width(&self) -> usize {
+        self.draft_width
+    }
+
+    pub(crate) fn cell_height(&self) -> usize {
+        self.render_mode.cell_height()
+    }
+
+    pub(crate) fn remaining_draft_rows(&self) -> usize {
+        self.draft_height - self.draft_rows_done
+    }
+
+    // An upper bound on the size of what `complete_rows` returns for `draft_rows` rows
+    pub(crate) fn max_completed_size(&self, draft_rows: usize) -> usize {
+        draft_rows.div_ceil(self.cell_height()) * self.width * self.max_cell_size
+    }
+
+    // Completes the cells covered by the next rows of the draft, returning their pixel data and
+    // colours. The rows must be whole, and no more than remain. Unless they are the last, they must
+    // also be a whole number of rows of cells.
+    pub(crate) fn complete_rows(&mut self, draft_pixel_data: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let cell_width = self.render_mode.cell_width();
+        let cell_height = self.render_mode.cell_height();
+        let draft_width = self.draft_width;
+        let draft_height = draft_pixel_data.len() / draft_width;
+        let height = draft_height.div_ceil(cell_height);
+        let width = self.width;
+
+        // Whole characters stand for single pixels, while dots are either inked or not
+        let levels = match self.render_mode {
+            RenderMode::Palette => self.palette.len(),
+            RenderMode::Quadrants | RenderMode::Braille => 2,
+        };
+        let indices = self.quantize(draft_height, draft_pixel_data, levels);
+
+        let mut pixel_data = String::with_capacity(height * width);
+        let mut colors = Vec::with_capacity(height * width * self.color_mode.cell_size());
+
+        for row in 0..height {
+            for col in 0..width {
+                let mut dots = 0;
+                let mut index = 0;
+                let mut ink_total = 0;
+                let mut pixels = 0;
+                for y in 0..cell_height {
+                    for x in 0..cell_width {
+                        let draft_row = row * cell_height + y;
+                        let draft_col = col * cell_width + x;
+                        if draft_row < draft_height && draft_col < draft_width {
+                            let i = draft_row * draft_width + draft_col;
+                            index = usize::from(indices[i]);
+                            if index > 0 {
+                                dots |= dot(self.render_mode, x, y);
+                            }
+                            ink_total += usize::from(draft_pixel_data[i]);
+                            pixels += 1;
+                        }
+                    }
+                }
+                pixel_data.push(match self.render_mode {
+                    RenderMode::Palette => self.palette[index],
+                    RenderMode::Quadrants => QUADRANTS[dots],
+                    RenderMode::Braille => char::from_u32(BRAILLE_BLANK + dots as u32).unwrap(),
+                });
+                if self.color_mode != ColorMode::Off {
+                    let ink = (ink_total / pixels) as u8;
+                    let (foreground, background) = colorize(ink, col, width);
+                    push_color(&mut colors, foreground, self.color_mode);
+                    push_color(&mut colors, background, self.color_mode);
+                }
+            }
+        }
+
+        self.draft_rows_done += draft_height;
+
+        (pixel_data.into_bytes(), colors)
+    }
+
+    // Reduces each pixel of the next `height` rows to one of `levels` evenly spaced levels, from
+    // blank to fully inked. There are at most 256 levels.
+    fn quantize(&mut self, height: usize, pixel_data: &[u8], levels: usize) -> Vec<u8> {
+        let width = self.draft_width;
+        let max_index = i32::try_from(levels - 1).unwrap();
+
+        let mut indices = Vec::with_capacity(pixel_data.len());
+
+        let mut errors = mem::take(&mut self.errors);
+        let mut next_errors = vec![0; width + 2];
+
+        for row in 0..height {
+            for col in 0..width {
+                let i = row * width + col;
+                let value = i32::from(pixel_data[i]) * SCALE;
+                let index = match self.dither {
+                    Dither::None => nearest(value, max_index),
+                    Dither::FloydSteinberg => {
+                        let value = value + errors[col + 1] / 16;
+                        let index = nearest(value, max_index);
+                        let error = value - value_of(index, max_index);
+                        errors[col + 2] += error * 7;
+                        next_errors[col] += error * 3;
+                        next_errors[col + 1] += error * 5;
+                        next_errors[col + 2] += error;
+                        index
+                    }
+                    Dither::Bayer => {
+                        let threshold = BAYER[(self.draft_rows_done + row) % 4][col % 4];
+                        ordered(value, threshold, max_index)
+                    }
+                };
+                indices.push(u8::try_from(index).unwrap());
+            }
+            mem::swap(&mut errors, &mut next_errors);
+            next_errors.fill(0);
+        }
+
+        self.errors = errors;
+
+        indices
+    }
+}
+
+// Quadrant blocks and Braille patterns all take this many bytes in UTF-8
+const BLOCK_CHAR_SIZE: usize = 3;
+
+// Indexed by a sum of `dot`s for `RenderMode::Quadrants`
+const QUADRANTS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+
+// The Braille pattern with no dots raised. Those with dots raised follow, with one bit per dot.
+const BRAILLE_BLANK: u32 = 0x2800;
+
+// Braille numbers its dots down the left column and then down the right, except for the bottom
+// row, which was added later
+const BRAILLE_DOTS: [[usize; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+// The bit that stands for the pixel at `x` and `y` within a cell
+fn dot(render_mode: RenderMode, x: usize, y: usize) -> usize {
+    match render_mode {
+        RenderMode::Palette => 0,
+        RenderMode::Quadrants => 1 << (y * 2 + x),
+        RenderMode::Braille => BRAILLE_DOTS[y][x],
+    }
+}
+
+// Grey levels are scaled up by this, so that fractions of them can be carried as errors. Only
+// integer arithmetic is used, so that dithering is reproducible everywhere.
+const SCALE: i32 = 16;
+
+const WHITE: i32 = u8::MAX as i32 * SCALE;
+
+const BAYER: [[i32; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+// The index of the palette entry nearest to `value`. Both ends of the palette are reached.
+fn nearest(value: i32, max_index: i32) -> i32 {
+    (value.clamp(0, WHITE) * max_index + WHITE / 2) / WHITE
+}
+
+// The value for which the palette entry at `index` stands
+fn value_of(index: i32, max_index: i32) -> i32 {
+    index * WHITE / max_index.max(1)
+}
+
+// Rounds `value` up or down to a palette entry, according to how far it lies between the two and
+// where `threshold` falls between 0 and 15. With a threshold halfway, this is `nearest`.
+fn ordered(value: i32, threshold: i32, max_index: i32) -> i32 {
+    (value * max_index * 32 + (2 * threshold + 1) * WHITE) / (WHITE * 32)
+}
+
+type Rgb = [u8; 3];
+
+// Characters run through the spectrum from left to right, over a background that glows in the same
+// hue in proportion to the ink's coverage, which is what draft pixels measure
+fn colorize(ink: u8, col: usize, width: usize) -> (Rgb, Rgb) {
+    let hue = col * HUE_STEPS / width;
+    (hue_to_rgb(hue, u8::MAX), hue_to_rgb(hue, ink / 4))
+}
+
+// Hues are divided into six sectors of 256 steps each
+const HUE_STEPS: usize = 6 * 256;
+
+// A fully saturated colour of brightness `value`
+fn hue_to_rgb(hue: usize, value: u8) -> Rgb {
+    let rise = (hue % 256) as u8;
+    let fall = u8::MAX - rise;
+    let [r, g, b] = match hue / 256 {
+        0 => [u8::MAX, rise, 0],
+        1 => [fall, u8::MAX, 0],
+        2 => [0, u8::MAX, rise],
+        3 => [0, fall, u8::MAX],
+        4 => [rise, 0, u8::MAX],
+        _ => [u8::MAX, 0, fall],
+    };
+    [r, g, b].map(|c| (u16::from(c) * u16::from(value) / u16::from(u8::MAX)) as u8)
+}
+
+fn push_color(colors: &mut Vec<u8>, rgb: Rgb, color_mode: ColorMode) {
+    match color_mode {
+        ColorMode::Off => {}
+        ColorMode::Ansi16 => colors.push(nearest_ansi16(rgb)),
+        ColorMode::Ansi256 => colors.push(nearest_ansi256(rgb)),
+        ColorMode::TrueColor => colors.extend_from_slice(&rgb),
+    }
+}
+
+// xterm's defaults
+const ANSI16: [Rgb; 16] = [
+    [0, 0, 0],
+    [205, 0, 0],
+    [0, 205, 0],
+    [205, 205, 0],
+    [0, 0, 238],
+    [205, 0, 205],
+    [0, 205, 205],
+    [229, 229, 229],
+    [127, 127, 127],
+    [255, 0, 0],
+    [0, 255, 0],
+    [255, 255, 0],
+    [92, 92, 255],
+    [255, 0, 255],
+    [0, 255, 255],
+    [255, 255, 255],
+];
+
+fn nearest_ansi16(rgb: Rgb) -> u8 {
+    let distance = |candidate: &Rgb| -> i32 {
+        rgb.iter()
+            .zip(candidate)
+            .map(|(a, b)| (i32::from(*a) - i32::from(*b)).pow(2))
+            .sum()
+    };
+    // Ties go to the lower index
+    (0..ANSI16.len())
+        .min_by_key(|i| distance(&ANSI16[*i]))
+        .unwrap() as u8
+}
+
+// The nearest entry of the 6x6x6 colour cube, which starts at index 16
+fn nearest_ansi256(rgb: Rgb) -> u8 {
+    let [r, g, b] = rgb.map(|c| (u16::from(c) * 5 + 127) / 255);
+    (16 + 36 * r + 6 * g + b) as u8
+}
+
+pub(crate) const fn palette(palette: BuiltInPalette) -> &'static str {
+    match palette {
+        BuiltInPalette::Short => "@%#x+=:-. ",
+        BuiltInPalette::Long => {
+            "$@B%8&WM#*oahkbdpqwmZO0QLCJUYXzcvunxrjft/\\|()1{}[]?-_+~<>i!lI;:,\"^`'. "
+        }
+        BuiltInPalette::Blocks => "█▓▒░ ",
+        BuiltInPalette::Inverted => " .-:=+x#%@",
+    }
+}
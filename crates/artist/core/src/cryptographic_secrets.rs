@@ -0,0 +1,54 @@
+//
+// Copyright 2023, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+use alloc::string::{String, ToString};
+use core::fmt;
+
+use banscii_artist_interface_types::SignatureAlgorithm;
+
+#[cfg(feature = "scheme-rsa")]
+mod rsa_pkcs1v15;
+#[cfg(feature = "scheme-rsa")]
+use rsa_pkcs1v15 as scheme;
+
+#[cfg(feature = "scheme-ed25519")]
+mod ed25519;
+#[cfg(feature = "scheme-ed25519")]
+use ed25519 as scheme;
+
+#[cfg(feature = "scheme-ecdsa-p256")]
+mod ecdsa_p256;
+#[cfg(feature = "scheme-ecdsa-p256")]
+use ecdsa_p256 as scheme;
+
+pub(crate) use scheme::Key;
+
+const PRIV_KEY_PEM: &str = include_str!(concat!(env!("OUT_DIR"), "/priv.pem"));
+
+// DER-encoded SubjectPublicKeyInfo
+pub(crate) const PUB_KEY_DER: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/pub.der"));
+
+pub(crate) const SIGNATURE_ALGORITHM: SignatureAlgorithm = scheme::SIGNATURE_ALGORITHM;
+
+// Decoding the key is expensive, so this is done once, at startup
+pub(crate) fn load_key() -> Result<Key, KeyError> {
+    Key::from_pem(PRIV_KEY_PEM)
+}
+
+#[derive(Debug)]
+pub struct KeyError(String);
+
+impl KeyError {
+    fn new(err: impl fmt::Display) -> Self {
+        Self(err.to_string())
+    }
+}
+
+impl fmt::Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid signing key: {}", self.0)
+    }
+}
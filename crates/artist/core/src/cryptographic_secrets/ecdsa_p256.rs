@@ -0,0 +1,51 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+use alloc::vec::Vec;
+
+use p256::ecdsa::signature::{Signer, Verifier};
+use p256::ecdsa::{Signature, SigningKey};
+use p256::pkcs8::DecodePrivateKey;
+
+use banscii_artist_interface_types::SignatureAlgorithm;
+
+use super::KeyError;
+
+pub(super) const SIGNATURE_ALGORITHM: SignatureAlgorithm = SignatureAlgorithm::EcdsaP256Sha256;
+
+pub(crate) struct Key {
+    signing_key: SigningKey,
+}
+
+impl Key {
+    pub(super) fn from_pem(priv_key_pem: &str) -> Result<Self, KeyError> {
+        Ok(Self {
+            signing_key: SigningKey::from_pkcs8_pem(priv_key_pem).map_err(KeyError::new)?,
+        })
+    }
+
+    // r and s, 32 bytes each
+    pub(crate) fn signature_size(&self) -> usize {
+        64
+    }
+
+    // Signatures are encoded as the fixed-size concatenation of r and s
+    pub(crate) fn sign(&self, data: &[u8]) -> Vec<u8> {
+        let signature: Signature = self.signing_key.sign(data);
+        signature.to_bytes().to_vec()
+    }
+
+    pub(crate) fn verify(&self, data: &[u8], signature: &[u8]) -> bool {
+        match Signature::from_slice(signature) {
+            Ok(signature) => self
+                .signing_key
+                .verifying_key()
+                .verify(data, &signature)
+                .is_ok(),
+            Err(_) => false,
+        }
+    }
+}
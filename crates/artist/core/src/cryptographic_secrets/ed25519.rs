@@ -0,0 +1,47 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+use alloc::vec::Vec;
+
+use ed25519_dalek::pkcs8::DecodePrivateKey;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, SIGNATURE_LENGTH};
+
+use banscii_artist_interface_types::SignatureAlgorithm;
+
+use super::KeyError;
+
+pub(super) const SIGNATURE_ALGORITHM: SignatureAlgorithm = SignatureAlgorithm::Ed25519;
+
+pub(crate) struct Key {
+    signing_key: SigningKey,
+}
+
+impl Key {
+    pub(super) fn from_pem(priv_key_pem: &str) -> Result<Self, KeyError> {
+        Ok(Self {
+            signing_key: SigningKey::from_pkcs8_pem(priv_key_pem).map_err(KeyError::new)?,
+        })
+    }
+
+    pub(crate) fn signature_size(&self) -> usize {
+        SIGNATURE_LENGTH
+    }
+
+    pub(crate) fn sign(&self, data: &[u8]) -> Vec<u8> {
+        self.signing_key.sign(data).to_vec()
+    }
+
+    pub(crate) fn verify(&self, data: &[u8], signature: &[u8]) -> bool {
+        match Signature::from_slice(signature) {
+            Ok(signature) => self
+                .signing_key
+                .verifying_key()
+                .verify(data, &signature)
+                .is_ok(),
+            Err(_) => false,
+        }
+    }
+}
@@ -0,0 +1,57 @@
+//
+// Copyright 2023, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+use alloc::vec::Vec;
+
+use rsa::pkcs1::DecodeRsaPrivateKey;
+use rsa::pkcs1v15::{Signature, SigningKey, VerifyingKey};
+use rsa::sha2::Sha256;
+use rsa::signature::{Signer, Verifier};
+use rsa::{PublicKeyParts, RsaPrivateKey};
+
+use banscii_artist_interface_types::SignatureAlgorithm;
+
+use super::KeyError;
+
+pub(super) const SIGNATURE_ALGORITHM: SignatureAlgorithm = SignatureAlgorithm::RsaPkcs1v15Sha256;
+
+pub(crate) struct Key {
+    signing_key: SigningKey<Sha256>,
+    verifying_key: VerifyingKey<Sha256>,
+    signature_size: usize,
+}
+
+impl Key {
+    pub(super) fn from_pem(priv_key_pem: &str) -> Result<Self, KeyError> {
+        let mut priv_key = RsaPrivateKey::from_pkcs1_pem(priv_key_pem).map_err(KeyError::new)?;
+        // CRT values speed up signing
+        priv_key.precompute().map_err(KeyError::new)?;
+        let verifying_key = VerifyingKey::new_with_prefix(priv_key.to_public_key());
+        let signature_size = priv_key.size();
+        let signing_key = SigningKey::new_with_prefix(priv_key);
+        Ok(Self {
+            signing_key,
+            verifying_key,
+            signature_size,
+        })
+    }
+
+    // Signatures are the size of the modulus
+    pub(crate) fn signature_size(&self) -> usize {
+        self.signature_size
+    }
+
+    pub(crate) fn sign(&self, data: &[u8]) -> Vec<u8> {
+        self.signing_key.sign(data).as_ref().to_vec()
+    }
+
+    pub(crate) fn verify(&self, data: &[u8], signature: &[u8]) -> bool {
+        match Signature::try_from(signature) {
+            Ok(signature) => self.verifying_key.verify(data, &signature).is_ok(),
+            Err(_) => false,
+        }
+    }
+}
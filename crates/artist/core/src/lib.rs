@@ -0,0 +1,782 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+// The artist's request handling, independent of seL4, so that it can also be built and exercised
+// on the host. The shared memory regions are presented as plain byte slices.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str;
+
+use banscii_artist_interface_types::*;
+
+mod artistic_secrets;
+mod cryptographic_secrets;
+mod memory_storage;
+mod persistent_state;
+mod policy;
+mod provenance;
+
+use artistic_secrets::Masterpiece;
+use persistent_state::{PersistentState, State};
+use policy::Policy;
+use provenance::MasterpieceHasher;
+
+pub use cryptographic_secrets::KeyError;
+pub use memory_storage::MemoryStorage;
+pub use persistent_state::{Storage, StorageError};
+
+pub struct Artist<S> {
+    persistent_state: PersistentState<S>,
+    policy: Policy,
+    key: cryptographic_secrets::Key,
+    // The completion or verification in progress, if any
+    transfer: Option<Transfer>,
+}
+
+enum Transfer {
+    Complete(Completion),
+    Verify(Verification),
+}
+
+struct Completion {
+    masterpiece: Masterpiece,
+    subject: String,
+    edition: u64,
+    hasher: MasterpieceHasher,
+}
+
+struct Verification {
+    height: usize,
+    width: usize,
+    subject: String,
+    edition: u64,
+    color_mode: ColorMode,
+    signature: Vec<u8>,
+    signature_algorithm: SignatureAlgorithm,
+    // How much of the masterpiece is still to come
+    pixels_remaining: usize,
+    colors_remaining: usize,
+    hasher: MasterpieceHasher,
+}
+
+impl<S: Storage> Artist<S> {
+    pub fn new(storage: S) -> Result<Self, InitError> {
+        Ok(Self {
+            persistent_state: PersistentState::load(storage).map_err(InitError::Storage)?,
+            policy: Policy::new(),
+            key: cryptographic_secrets::load_key().map_err(InitError::Key)?,
+            transfer: None,
+        })
+    }
+
+    // `region_in` and `region_out` hold the contents of the regions shared with the assistant.
+    // Every range in `req` is checked against them, so no request can cause a panic.
+    //
+    // Requests that make up a completion or verification take the transfer in progress out of
+    // `self.transfer`, and only put it back once they have succeeded.
+    pub fn handle_request(
+        &mut self,
+        req: Request,
+        now_ms: u64,
+        region_in: &[u8],
+        region_out: &mut [u8],
+    ) -> Result<Response, ArtistError> {
+        Ok(match req {
+            Request::BeginComplete(req) => Response::BeginComplete(self.begin_complete(
+                &req,
+                now_ms,
+                region_in,
+                region_out.len(),
+            )?),
+            Request::CompleteChunk(req) => {
+                Response::CompleteChunk(self.complete_chunk(&req, region_in, region_out)?)
+            }
+            Request::FinishComplete => Response::FinishComplete(self.finish_complete(region_out)?),
+            Request::BeginVerify(req) => {
+                self.begin_verify(&req, region_in)?;
+                Response::BeginVerify
+            }
+            Request::VerifyChunk(req) => {
+                self.verify_chunk(&req, region_in)?;
+                Response::VerifyChunk
+            }
+            Request::FinishVerify => Response::FinishVerify(self.finish_verify()?),
+            Request::GetPublicKey => Response::GetPublicKey(self.get_public_key(region_out)?),
+            Request::GetStatus => Response::GetStatus(self.get_status(now_ms)),
+        })
+    }
+
+    fn begin_complete(
+        &mut self,
+        req: &BeginCompleteRequest,
+        now_ms: u64,
+        region_in: &[u8],
+        region_out_size: usize,
+    ) -> Result<BeginCompleteResponse, ArtistError> {
+        self.transfer = None;
+
+        if req.height == 0 || req.width == 0 {
+            return Err(ArtistError::Malformed);
+        }
+        if req.height > MAX_DRAFT_HEIGHT || req.width > MAX_DRAFT_WIDTH {
+            return Err(ArtistError::TooLarge);
+        }
+        let subject = read_subject(region_in, req.subject_start, req.subject_size)?;
+        let palette = match req.palette {
+            Palette::BuiltIn(palette) => artistic_secrets::palette(palette).chars().collect(),
+            Palette::Custom { start, size } => read_custom_palette(region_in, start, size)?,
+        };
+
+        let masterpiece = Masterpiece::new(
+            req.height,
+            req.width,
+            palette,
+            req.dither,
+            req.render_mode,
+            req.color_mode,
+        );
+
+        // Refuse now, rather than after claiming an edition, if not even one row of cells could be
+        // sent there and back
+        let cell_height = req.render_mode.cell_height();
+        if cell_height * req.width > region_in.len()
+            || masterpiece.max_completed_size(cell_height) > region_out_size
+            || self.key.signature_size() > region_out_size
+        {
+            return Err(ArtistError::TooLarge);
+        }
+
+        self.policy
+            .claim(now_ms, self.persistent_state.get().next_edition)?;
+
+        // Claim the edition before anything leaves the artist, so that a reboot can never cause an
+        // edition to be issued twice
+        let edition = self.persistent_state.get().next_edition;
+        self.persistent_state
+            .set(State {
+                next_edition: edition + 1,
+            })
+            .map_err(|_| ArtistError::Storage)?;
+
+        let resp = BeginCompleteResponse {
+            height: masterpiece.height,
+            width: masterpiece.width,
+            edition,
+        };
+
+        self.transfer = Some(Transfer::Complete(Completion {
+            masterpiece,
+            subject,
+            edition,
+            hasher: MasterpieceHasher::default(),
+        }));
+
+        Ok(resp)
+    }
+
+    fn complete_chunk(
+        &mut self,
+        req: &CompleteChunkRequest,
+        region_in: &[u8],
+        region_out: &mut [u8],
+    ) -> Result<CompleteChunkResponse, ArtistError> {
+        let Some(Transfer::Complete(mut completion)) = self.transfer.take() else {
+            return Err(ArtistError::Malformed);
+        };
+        let masterpiece = &mut completion.masterpiece;
+
+        let draft = read_region(region_in, req.draft_start, req.draft_size)?;
+        let draft_width = masterpiece.draft_width();
+        let rows = draft.len() / draft_width;
+        let remaining = masterpiece.remaining_draft_rows();
+        if draft.len() % draft_width != 0
+            || rows > remaining
+            || (rows < remaining && rows % masterpiece.cell_height() != 0)
+        {
+            return Err(ArtistError::Malformed);
+        }
+        if masterpiece.max_completed_size(rows) > region_out.len() {
+            return Err(ArtistError::TooLarge);
+        }
+
+        let (pixel_data, colors) = masterpiece.complete_rows(draft);
+        completion.hasher.update(&pixel_data, &colors);
+
+        let masterpiece_start = 0;
+        let masterpiece_size = pixel_data.len();
+        let masterpiece_end = masterpiece_start + masterpiece_size;
+
+        region_out[masterpiece_start..masterpiece_end].copy_from_slice(&pixel_data);
+
+        let colors_start = masterpiece_end;
+        let colors_size = colors.len();
+        let colors_end = colors_start + colors_size;
+
+        region_out[colors_start..colors_end].copy_from_slice(&colors);
+
+        self.transfer = Some(Transfer::Complete(completion));
+
+        Ok(CompleteChunkResponse {
+            masterpiece_start,
+            masterpiece_size,
+            colors_start,
+            colors_size,
+        })
+    }
+
+    fn finish_complete(
+        &mut self,
+        region_out: &mut [u8],
+    ) -> Result<FinishCompleteResponse, ArtistError> {
+        let Some(Transfer::Complete(completion)) = self.transfer.take() else {
+            return Err(ArtistError::Malformed);
+        };
+        let masterpiece = &completion.masterpiece;
+        if masterpiece.remaining_draft_rows() != 0 {
+            return Err(ArtistError::Malformed);
+        }
+
+        let signature = self.key.sign(&completion.hasher.signed_data(
+            masterpiece.height,
+            masterpiece.width,
+            &completion.subject,
+            completion.edition,
+            masterpiece.color_mode,
+        ));
+
+        let signature_start = 0;
+        let signature_size = signature.len();
+        let signature_end = signature_start + signature_size;
+
+        region_out
+            .get_mut(signature_start..signature_end)
+            .ok_or(ArtistError::TooLarge)?
+            .copy_from_slice(&signature);
+
+        Ok(FinishCompleteResponse {
+            signature_start,
+            signature_size,
+            signature_algorithm: cryptographic_secrets::SIGNATURE_ALGORITHM,
+        })
+    }
+
+    fn begin_verify(
+        &mut self,
+        req: &BeginVerifyRequest,
+        region_in: &[u8],
+    ) -> Result<(), ArtistError> {
+        self.transfer = None;
+
+        let subject = read_subject(region_in, req.subject_start, req.subject_size)?;
+        let signature = read_region(region_in, req.signature_start, req.signature_size)?.to_vec();
+        let pixels = req
+            .height
+            .checked_mul(req.width)
+            .ok_or(ArtistError::Malformed)?;
+        let colors = pixels
+            .checked_mul(req.color_mode.cell_size())
+            .ok_or(ArtistError::Malformed)?;
+
+        self.transfer = Some(Transfer::Verify(Verification {
+            height: req.height,
+            width: req.width,
+            subject,
+            edition: req.edition,
+            color_mode: req.color_mode,
+            signature,
+            signature_algorithm: req.signature_algorithm,
+            pixels_remaining: pixels,
+            colors_remaining: colors,
+            hasher: MasterpieceHasher::default(),
+        }));
+
+        Ok(())
+    }
+
+    fn verify_chunk(
+        &mut self,
+        req: &VerifyChunkRequest,
+        region_in: &[u8],
+    ) -> Result<(), ArtistError> {
+        let Some(Transfer::Verify(mut verification)) = self.transfer.take() else {
+            return Err(ArtistError::Malformed);
+        };
+
+        // Masterpieces are UTF-8, with a char per pixel
+        let pixel_data = read_region(region_in, req.masterpiece_start, req.masterpiece_size)?;
+        let pixels = str::from_utf8(pixel_data)
+            .map_err(|_| ArtistError::Malformed)?
+            .chars()
+            .count();
+        let colors = read_region(region_in, req.colors_start, req.colors_size)?;
+
+        verification.pixels_remaining = verification
+            .pixels_remaining
+            .checked_sub(pixels)
+            .ok_or(ArtistError::Malformed)?;
+        verification.colors_remaining = verification
+            .colors_remaining
+            .checked_sub(colors.len())
+            .ok_or(ArtistError::Malformed)?;
+        verification.hasher.update(pixel_data, colors);
+
+        self.transfer = Some(Transfer::Verify(verification));
+
+        Ok(())
+    }
+
+    fn finish_verify(&mut self) -> Result<VerifyResponse, ArtistError> {
+        let Some(Transfer::Verify(verification)) = self.transfer.take() else {
+            return Err(ArtistError::Malformed);
+        };
+        if verification.pixels_remaining != 0 || verification.colors_remaining != 0 {
+            return Err(ArtistError::Malformed);
+        }
+
+        let signed_data = verification.hasher.signed_data(
+            verification.height,
+            verification.width,
+            &verification.subject,
+            verification.edition,
+            verification.color_mode,
+        );
+
+        Ok(VerifyResponse {
+            valid: verification.signature_algorithm == cryptographic_secrets::SIGNATURE_ALGORITHM
+                && self.key.verify(&signed_data, &verification.signature),
+        })
+    }
+
+    fn get_public_key(
+        &mut self,
+        region_out: &mut [u8],
+    ) -> Result<GetPublicKeyResponse, ArtistError> {
+        let public_key = cryptographic_secrets::PUB_KEY_DER;
+
+        let public_key_start = 0;
+        let public_key_size = public_key.len();
+        let public_key_end = public_key_start + public_key_size;
+
+        region_out
+            .get_mut(public_key_start..public_key_end)
+            .ok_or(ArtistError::TooLarge)?
+            .copy_from_slice(public_key);
+
+        Ok(GetPublicKeyResponse {
+            public_key_start,
+            public_key_size,
+            signature_algorithm: cryptographic_secrets::SIGNATURE_ALGORITHM,
+        })
+    }
+
+    fn get_status(&mut self, now_ms: u64) -> GetStatusResponse {
+        GetStatusResponse {
+            window_remaining: self.policy.window_remaining(now_ms),
+            window_limit: policy::MAX_SIGNATURES_PER_WINDOW,
+            window_ms: policy::WINDOW_MS,
+            window_resets_in_ms: self.policy.window_resets_in_ms(now_ms),
+            lifetime_remaining: policy::lifetime_remaining(
+                self.persistent_state.get().next_edition,
+            ),
+            lifetime_quota: policy::LIFETIME_QUOTA,
+        }
+    }
+}
+
+fn read_subject(region: &[u8], start: usize, size: usize) -> Result<String, ArtistError> {
+    String::from_utf8(read_region(region, start, size)?.to_vec())
+        .map_err(|_| ArtistError::Malformed)
+}
+
+fn read_custom_palette(region: &[u8], start: usize, size: usize) -> Result<Vec<char>, ArtistError> {
+    let palette = str::from_utf8(read_region(region, start, size)?)
+        .map_err(|_| ArtistError::Malformed)?
+        .chars()
+        .collect::<Vec<_>>();
+    if !(MIN_CUSTOM_PALETTE_LEN..=MAX_CUSTOM_PALETTE_LEN).contains(&palette.len())
+        || palette.iter().any(|c| c.is_control())
+    {
+        return Err(ArtistError::Malformed);
+    }
+    Ok(palette)
+}
+
+// `start` and `size` come from the untrusted assistant, so they are checked before any memory is
+// allocated or accessed
+fn read_region(region: &[u8], start: usize, size: usize) -> Result<&[u8], ArtistError> {
+    start
+        .checked_add(size)
+        .and_then(|end| region.get(start..end))
+        .ok_or(ArtistError::OutOfBounds)
+}
+
+#[derive(Debug)]
+pub enum InitError {
+    Storage(StorageError),
+    Key(KeyError),
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Storage(err) => err.fmt(f),
+            Self::Key(err) => err.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    const REGION_SIZE: usize = 0x4_000;
+
+    const SUBJECT: &[u8] = b"Test";
+
+    struct Harness {
+        artist: Artist<MemoryStorage>,
+        region_out: Vec<u8>,
+    }
+
+    impl Harness {
+        fn new() -> Self {
+            Self {
+                artist: Artist::new(MemoryStorage::new()).unwrap(),
+                region_out: vec![0; REGION_SIZE],
+            }
+        }
+
+        // `region_in` is padded to the usual size
+        fn call(&mut self, req: Request, region_in: &[u8]) -> Result<Response, ArtistError> {
+            let mut region_in = region_in.to_vec();
+            region_in.resize(REGION_SIZE, 0);
+            self.artist
+                .handle_request(req, 0, &region_in, &mut self.region_out)
+        }
+
+        fn out(&self, start: usize, size: usize) -> Vec<u8> {
+            self.region_out[start..][..size].to_vec()
+        }
+
+        fn begin(&mut self, height: usize, width: usize, render_mode: RenderMode) -> u64 {
+            match self.call(begin_complete(height, width, render_mode), SUBJECT) {
+                Ok(Response::BeginComplete(resp)) => resp.edition,
+                resp => panic!("{resp:?}"),
+            }
+        }
+
+        fn chunk(&mut self, draft: &[u8]) -> Result<Response, ArtistError> {
+            self.call(
+                Request::CompleteChunk(CompleteChunkRequest {
+                    draft_start: 0,
+                    draft_size: draft.len(),
+                }),
+                draft,
+            )
+        }
+    }
+
+    fn begin_complete(height: usize, width: usize, render_mode: RenderMode) -> Request {
+        Request::BeginComplete(BeginCompleteRequest {
+            height,
+            width,
+            subject_start: 0,
+            subject_size: SUBJECT.len(),
+            palette: Palette::BuiltIn(BuiltInPalette::Short),
+            dither: Dither::None,
+            render_mode,
+            color_mode: ColorMode::Off,
+        })
+    }
+
+    fn draft(height: usize, width: usize) -> Vec<u8> {
+        (0..height * width).map(|i| (i * 37) as u8).collect()
+    }
+
+    #[test]
+    fn read_region_bounds() {
+        let region = [0; REGION_SIZE];
+        assert_eq!(
+            read_region(&region, 0, REGION_SIZE).unwrap().len(),
+            REGION_SIZE
+        );
+        assert_eq!(read_region(&region, REGION_SIZE, 0), Ok(&[][..]));
+        assert_eq!(
+            read_region(&region, 1, REGION_SIZE),
+            Err(ArtistError::OutOfBounds)
+        );
+        assert_eq!(
+            read_region(&region, REGION_SIZE + 1, 0),
+            Err(ArtistError::OutOfBounds)
+        );
+    }
+
+    #[test]
+    fn read_region_overflow() {
+        let region = [0; REGION_SIZE];
+        assert_eq!(
+            read_region(&region, usize::MAX, 2),
+            Err(ArtistError::OutOfBounds)
+        );
+        assert_eq!(
+            read_region(&region, 2, usize::MAX),
+            Err(ArtistError::OutOfBounds)
+        );
+        assert_eq!(
+            read_region(&region, usize::MAX, usize::MAX),
+            Err(ArtistError::OutOfBounds)
+        );
+    }
+
+    #[test]
+    fn ranges_beyond_region() {
+        let mut harness = Harness::new();
+        let req = Request::BeginComplete(BeginCompleteRequest {
+            subject_start: REGION_SIZE - 1,
+            subject_size: 2,
+            ..match begin_complete(1, 1, RenderMode::Palette) {
+                Request::BeginComplete(req) => req,
+                _ => unreachable!(),
+            }
+        });
+        assert_eq!(
+            harness.call(req, SUBJECT).unwrap_err(),
+            ArtistError::OutOfBounds
+        );
+
+        harness.begin(2, 2, RenderMode::Palette);
+        let req = Request::CompleteChunk(CompleteChunkRequest {
+            draft_start: usize::MAX,
+            draft_size: 2,
+        });
+        assert_eq!(
+            harness.call(req, &[]).unwrap_err(),
+            ArtistError::OutOfBounds
+        );
+
+        let req = Request::BeginVerify(BeginVerifyRequest {
+            height: 1,
+            width: 1,
+            subject_start: 0,
+            subject_size: 0,
+            edition: 1,
+            color_mode: ColorMode::Off,
+            signature_start: 1,
+            signature_size: REGION_SIZE,
+            signature_algorithm: cryptographic_secrets::SIGNATURE_ALGORITHM,
+        });
+        assert_eq!(
+            harness.call(req, &[]).unwrap_err(),
+            ArtistError::OutOfBounds
+        );
+    }
+
+    #[test]
+    fn chunks_without_begin() {
+        let mut harness = Harness::new();
+        assert_eq!(harness.chunk(&[0]).unwrap_err(), ArtistError::Malformed);
+        assert_eq!(
+            harness.call(Request::FinishComplete, &[]).unwrap_err(),
+            ArtistError::Malformed
+        );
+        let req = Request::VerifyChunk(VerifyChunkRequest {
+            masterpiece_start: 0,
+            masterpiece_size: 0,
+            colors_start: 0,
+            colors_size: 0,
+        });
+        assert_eq!(harness.call(req, &[]).unwrap_err(), ArtistError::Malformed);
+        assert_eq!(
+            harness.call(Request::FinishVerify, &[]).unwrap_err(),
+            ArtistError::Malformed
+        );
+    }
+
+    #[test]
+    fn chunks_out_of_order() {
+        let mut harness = Harness::new();
+
+        // Verification requests do not follow on from a completion
+        harness.begin(2, 2, RenderMode::Palette);
+        assert_eq!(
+            harness.call(Request::FinishVerify, &[]).unwrap_err(),
+            ArtistError::Malformed
+        );
+        // ...and the error abandoned the completion
+        assert_eq!(
+            harness.chunk(&draft(2, 2)).unwrap_err(),
+            ArtistError::Malformed
+        );
+
+        // Finishing before every row has been sent
+        harness.begin(2, 2, RenderMode::Palette);
+        harness.chunk(&draft(1, 2)).unwrap();
+        assert_eq!(
+            harness.call(Request::FinishComplete, &[]).unwrap_err(),
+            ArtistError::Malformed
+        );
+
+        // More rows than remain
+        harness.begin(2, 2, RenderMode::Palette);
+        harness.chunk(&draft(1, 2)).unwrap();
+        assert_eq!(
+            harness.chunk(&draft(2, 2)).unwrap_err(),
+            ArtistError::Malformed
+        );
+
+        // Partial rows
+        harness.begin(2, 2, RenderMode::Palette);
+        assert_eq!(harness.chunk(&[0; 3]).unwrap_err(), ArtistError::Malformed);
+
+        // Partial rows of cells, except at the end
+        harness.begin(8, 2, RenderMode::Braille);
+        assert_eq!(
+            harness.chunk(&draft(2, 2)).unwrap_err(),
+            ArtistError::Malformed
+        );
+        harness.begin(6, 2, RenderMode::Braille);
+        harness.chunk(&draft(4, 2)).unwrap();
+        harness.chunk(&draft(2, 2)).unwrap();
+        harness.call(Request::FinishComplete, &[]).unwrap();
+
+        // Finishing twice
+        assert_eq!(
+            harness.call(Request::FinishComplete, &[]).unwrap_err(),
+            ArtistError::Malformed
+        );
+    }
+
+    #[test]
+    fn begin_checks_draft() {
+        let mut harness = Harness::new();
+        for (height, width, err) in [
+            (0, 1, ArtistError::Malformed),
+            (1, 0, ArtistError::Malformed),
+            (1, MAX_DRAFT_WIDTH + 1, ArtistError::TooLarge),
+            (MAX_DRAFT_HEIGHT + 1, 1, ArtistError::TooLarge),
+            (usize::MAX, MAX_DRAFT_WIDTH, ArtistError::TooLarge),
+        ] {
+            let req = begin_complete(height, width, RenderMode::Braille);
+            assert_eq!(
+                harness.call(req, SUBJECT).unwrap_err(),
+                err,
+                "{height}x{width}"
+            );
+        }
+        // The largest draft is fine, so long as it arrives in chunks
+        let req = begin_complete(MAX_DRAFT_HEIGHT, MAX_DRAFT_WIDTH, RenderMode::Braille);
+        assert!(harness.call(req, SUBJECT).is_ok());
+    }
+
+    #[test]
+    fn abandoned_completion_skips_edition() {
+        let mut harness = Harness::new();
+        assert_eq!(harness.begin(1, 1, RenderMode::Palette), 1);
+        assert_eq!(harness.begin(1, 1, RenderMode::Palette), 2);
+    }
+
+    #[test]
+    fn status_does_not_abandon_completion() {
+        let mut harness = Harness::new();
+        harness.begin(1, 1, RenderMode::Palette);
+        harness.call(Request::GetStatus, &[]).unwrap();
+        harness.call(Request::GetPublicKey, &[]).unwrap();
+        harness.chunk(&[0]).unwrap();
+        harness.call(Request::FinishComplete, &[]).unwrap();
+    }
+
+    // Completes a draft a few rows at a time, then verifies the masterpiece likewise, with
+    // `tamper` applied to its pixel data
+    fn round_trip(render_mode: RenderMode, tamper: impl Fn(&mut Vec<u8>)) -> bool {
+        let (height, width) = (11, 7);
+        let mut harness = Harness::new();
+        let edition = harness.begin(height, width, render_mode);
+
+        let resp = match harness.call(Request::GetStatus, &[]) {
+            Ok(Response::GetStatus(resp)) => resp,
+            resp => panic!("{resp:?}"),
+        };
+        assert_eq!(resp.window_remaining, policy::MAX_SIGNATURES_PER_WINDOW - 1);
+
+        let mut pixel_data = Vec::new();
+        for chunk in draft(height, width).chunks(4 * width) {
+            match harness.chunk(chunk) {
+                Ok(Response::CompleteChunk(resp)) => {
+                    pixel_data.extend(harness.out(resp.masterpiece_start, resp.masterpiece_size));
+                    assert_eq!(resp.colors_size, 0);
+                }
+                resp => panic!("{resp:?}"),
+            }
+        }
+        let (signature, signature_algorithm) = match harness.call(Request::FinishComplete, &[]) {
+            Ok(Response::FinishComplete(resp)) => (
+                harness.out(resp.signature_start, resp.signature_size),
+                resp.signature_algorithm,
+            ),
+            resp => panic!("{resp:?}"),
+        };
+
+        tamper(&mut pixel_data);
+
+        let mut region_in = SUBJECT.to_vec();
+        region_in.extend(&signature);
+        let req = Request::BeginVerify(BeginVerifyRequest {
+            height: height.div_ceil(render_mode.cell_height()),
+            width: width.div_ceil(render_mode.cell_width()),
+            subject_start: 0,
+            subject_size: SUBJECT.len(),
+            edition,
+            color_mode: ColorMode::Off,
+            signature_start: SUBJECT.len(),
+            signature_size: signature.len(),
+            signature_algorithm,
+        });
+        harness.call(req, &region_in).unwrap();
+        let pixel_data = String::from_utf8(pixel_data).unwrap();
+        let mut rest = pixel_data.as_str();
+        while !rest.is_empty() {
+            let mut size = rest.len().min(5);
+            while !rest.is_char_boundary(size) {
+                size += 1;
+            }
+            let (chunk, after) = rest.split_at(size);
+            rest = after;
+            let req = Request::VerifyChunk(VerifyChunkRequest {
+                masterpiece_start: 0,
+                masterpiece_size: chunk.len(),
+                colors_start: 0,
+                colors_size: 0,
+            });
+            harness.call(req, chunk.as_bytes()).unwrap();
+        }
+        match harness.call(Request::FinishVerify, &[]) {
+            Ok(Response::FinishVerify(resp)) => resp.valid,
+            resp => panic!("{resp:?}"),
+        }
+    }
+
+    #[test]
+    fn completed_masterpiece_verifies() {
+        for render_mode in RenderMode::ALL {
+            assert!(round_trip(*render_mode, |_| {}));
+        }
+    }
+
+    #[test]
+    fn tampered_masterpiece_does_not_verify() {
+        assert!(!round_trip(RenderMode::Palette, |pixel_data| {
+            pixel_data[0] = if pixel_data[0] == b'@' { b'%' } else { b'@' };
+        }));
+    }
+}
@@ -0,0 +1,61 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{Storage, StorageError};
+
+// Stands in for flash in tests and fuzzing. Like NOR flash, erasing sets bits, and programming can
+// only clear them.
+pub struct MemoryStorage {
+    bytes: Vec<u8>,
+}
+
+impl MemoryStorage {
+    pub const SECTOR_SIZE: usize = 0x1_000;
+
+    // Two erased sectors, as the artist expects
+    pub fn new() -> Self {
+        Self {
+            bytes: vec![0xff; 2 * Self::SECTOR_SIZE],
+        }
+    }
+
+    // For simulating torn writes and corruption
+    pub fn bytes_mut(&mut self) -> &mut [u8] {
+        &mut self.bytes
+    }
+}
+
+impl Default for MemoryStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn sector_size(&self) -> usize {
+        Self::SECTOR_SIZE
+    }
+
+    fn read(&self, offset: usize, buf: &mut [u8]) -> Result<(), StorageError> {
+        buf.copy_from_slice(&self.bytes[offset..][..buf.len()]);
+        Ok(())
+    }
+
+    fn erase_sector(&mut self, sector: usize) -> Result<(), StorageError> {
+        self.bytes[sector * Self::SECTOR_SIZE..][..Self::SECTOR_SIZE].fill(0xff);
+        Ok(())
+    }
+
+    fn program(&mut self, offset: usize, data: &[u8]) -> Result<(), StorageError> {
+        for (b, d) in self.bytes[offset..].iter_mut().zip(data) {
+            *b &= d;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,319 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+// State that must survive reboots, kept as an append-only log of records in flash-like storage.
+// Each update programs a new record into the active sector. When that sector fills up, the other
+// sector is erased and becomes the active one. On startup, the valid record with the highest
+// sequence number wins, so an interrupted write or erase at worst loses the update in progress.
+
+use core::fmt;
+
+pub trait Storage {
+    fn sector_size(&self) -> usize;
+
+    fn read(&self, offset: usize, buf: &mut [u8]) -> Result<(), StorageError>;
+
+    // Sets every byte of the sector to 0xff
+    fn erase_sector(&mut self, sector: usize) -> Result<(), StorageError>;
+
+    // The target must have been erased. `offset` and `data.len()` must be multiples of 4.
+    fn program(&mut self, offset: usize, data: &[u8]) -> Result<(), StorageError>;
+}
+
+#[derive(Debug)]
+pub struct StorageError;
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "persistent storage error")
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct State {
+    pub(crate) next_edition: u64,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self { next_edition: 1 }
+    }
+}
+
+pub(crate) struct PersistentState<S> {
+    storage: S,
+    state: State,
+    sequence: u32,
+    sector: usize,
+    next_slot: usize,
+}
+
+const NUM_SECTORS: usize = 2;
+
+// Record layout (little-endian):
+//   0: magic
+//   4: sequence number
+//   8: next_edition
+//  16: checksum of the preceding bytes
+const RECORD_SIZE: usize = 20;
+const RECORD_MAGIC: u32 = 0xba5c_0001;
+
+impl<S: Storage> PersistentState<S> {
+    pub(crate) fn load(mut storage: S) -> Result<Self, StorageError> {
+        let slots_per_sector = storage.sector_size() / RECORD_SIZE;
+        let mut latest: Option<(u32, State, usize)> = None;
+        let mut next_slots = [0; NUM_SECTORS];
+        for (sector, next_slot) in next_slots.iter_mut().enumerate() {
+            for slot in 0..slots_per_sector {
+                let mut record = [0; RECORD_SIZE];
+                storage.read(
+                    sector * storage.sector_size() + slot * RECORD_SIZE,
+                    &mut record,
+                )?;
+                if record.iter().all(|b| *b == 0xff) {
+                    continue;
+                }
+                *next_slot = slot + 1;
+                if let Some((sequence, state)) = decode_record(&record) {
+                    let newer = match latest {
+                        Some((latest_sequence, ..)) => sequence > latest_sequence,
+                        None => true,
+                    };
+                    if newer {
+                        latest = Some((sequence, state, sector));
+                    }
+                }
+            }
+        }
+        Ok(match latest {
+            Some((sequence, state, sector)) => Self {
+                storage,
+                state,
+                sequence,
+                sector,
+                next_slot: next_slots[sector],
+            },
+            None => {
+                storage.erase_sector(0)?;
+                Self {
+                    storage,
+                    state: State::default(),
+                    sequence: 0,
+                    sector: 0,
+                    next_slot: 0,
+                }
+            }
+        })
+    }
+
+    pub(crate) fn get(&self) -> &State {
+        &self.state
+    }
+
+    pub(crate) fn set(&mut self, state: State) -> Result<(), StorageError> {
+        let sector_size = self.storage.sector_size();
+        if self.next_slot == sector_size / RECORD_SIZE {
+            let sector = (self.sector + 1) % NUM_SECTORS;
+            self.storage.erase_sector(sector)?;
+            self.sector = sector;
+            self.next_slot = 0;
+        }
+        let sequence = self.sequence.wrapping_add(1);
+        self.storage.program(
+            self.sector * sector_size + self.next_slot * RECORD_SIZE,
+            &encode_record(sequence, &state),
+        )?;
+        self.next_slot += 1;
+        self.sequence = sequence;
+        self.state = state;
+        Ok(())
+    }
+}
+
+fn encode_record(sequence: u32, state: &State) -> [u8; RECORD_SIZE] {
+    let mut record = [0; RECORD_SIZE];
+    record[0..4].copy_from_slice(&RECORD_MAGIC.to_le_bytes());
+    record[4..8].copy_from_slice(&sequence.to_le_bytes());
+    record[8..16].copy_from_slice(&state.next_edition.to_le_bytes());
+    let checksum = checksum(&record[..16]);
+    record[16..20].copy_from_slice(&checksum.to_le_bytes());
+    record
+}
+
+fn decode_record(record: &[u8; RECORD_SIZE]) -> Option<(u32, State)> {
+    let word = |i: usize| u32::from_le_bytes(record[i..i + 4].try_into().unwrap());
+    if word(0) != RECORD_MAGIC || word(16) != checksum(&record[..16]) {
+        return None;
+    }
+    let next_edition = u64::from_le_bytes(record[8..16].try_into().unwrap());
+    Some((word(4), State { next_edition }))
+}
+
+// FNV-1a
+fn checksum(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c_9dc5, |acc, b| {
+        (acc ^ u32::from(*b)).wrapping_mul(0x0100_0193)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryStorage;
+
+    const SLOTS_PER_SECTOR: usize = MemoryStorage::SECTOR_SIZE / RECORD_SIZE;
+
+    fn reload(state: PersistentState<MemoryStorage>) -> PersistentState<MemoryStorage> {
+        PersistentState::load(state.storage).unwrap()
+    }
+
+    fn set_edition(state: &mut PersistentState<MemoryStorage>, next_edition: u64) {
+        state.set(State { next_edition }).unwrap();
+    }
+
+    fn slot(state: &mut PersistentState<MemoryStorage>, sector: usize, slot: usize) -> &mut [u8] {
+        &mut state.storage.bytes_mut()[sector * MemoryStorage::SECTOR_SIZE + slot * RECORD_SIZE..]
+            [..RECORD_SIZE]
+    }
+
+    #[test]
+    fn starts_from_first_edition() {
+        let state = PersistentState::load(MemoryStorage::new()).unwrap();
+        assert_eq!(state.get().next_edition, 1);
+        assert_eq!(reload(state).get().next_edition, 1);
+    }
+
+    #[test]
+    fn survives_reload() {
+        let mut state = PersistentState::load(MemoryStorage::new()).unwrap();
+        for next_edition in 2..10 {
+            set_edition(&mut state, next_edition);
+            state = reload(state);
+            assert_eq!(state.get().next_edition, next_edition);
+        }
+    }
+
+    #[test]
+    fn switches_sectors() {
+        let mut state = PersistentState::load(MemoryStorage::new()).unwrap();
+        for next_edition in 2..2 + SLOTS_PER_SECTOR as u64 {
+            set_edition(&mut state, next_edition);
+        }
+        assert_eq!(state.sector, 0);
+        assert_eq!(state.next_slot, SLOTS_PER_SECTOR);
+
+        // A full sector survives a reload, and the next update moves on to the other sector
+        state = reload(state);
+        let next_edition = 2 + SLOTS_PER_SECTOR as u64;
+        set_edition(&mut state, next_edition);
+        assert_eq!((state.sector, state.next_slot), (1, 1));
+        assert_eq!(reload(state).get().next_edition, next_edition);
+    }
+
+    #[test]
+    fn wraps_around_sectors() {
+        let mut state = PersistentState::load(MemoryStorage::new()).unwrap();
+        let last = 1 + 3 * SLOTS_PER_SECTOR as u64;
+        for next_edition in 2..=last {
+            set_edition(&mut state, next_edition);
+        }
+        assert_eq!(state.sector, 0);
+        state = reload(state);
+        assert_eq!(state.get().next_edition, last);
+        set_edition(&mut state, last + 1);
+        assert_eq!(reload(state).get().next_edition, last + 1);
+    }
+
+    #[test]
+    fn ignores_torn_record() {
+        let mut state = PersistentState::load(MemoryStorage::new()).unwrap();
+        set_edition(&mut state, 2);
+        set_edition(&mut state, 3);
+        // Power was lost while the second record was being programmed, so only some of its bits
+        // were cleared
+        slot(&mut state, 0, 1)[8..].fill(0xff);
+        state = reload(state);
+        assert_eq!(state.get().next_edition, 2);
+
+        // The torn slot is skipped rather than programmed over
+        set_edition(&mut state, 3);
+        assert_eq!(state.next_slot, 3);
+        assert_eq!(reload(state).get().next_edition, 3);
+    }
+
+    #[test]
+    fn ignores_corrupt_record() {
+        let mut state = PersistentState::load(MemoryStorage::new()).unwrap();
+        set_edition(&mut state, 2);
+        set_edition(&mut state, 3);
+        slot(&mut state, 0, 1)[8] ^= 0x01;
+        assert_eq!(reload(state).get().next_edition, 2);
+    }
+
+    #[test]
+    fn ignores_record_without_magic() {
+        let mut state = PersistentState::load(MemoryStorage::new()).unwrap();
+        set_edition(&mut state, 2);
+        let mut record = encode_record(2, &State { next_edition: 3 });
+        record[0] = 0;
+        let checksum = checksum(&record[..16]);
+        record[16..].copy_from_slice(&checksum.to_le_bytes());
+        slot(&mut state, 0, 1).copy_from_slice(&record);
+        assert_eq!(reload(state).get().next_edition, 2);
+    }
+
+    #[test]
+    fn survives_interrupted_sector_switch() {
+        let mut state = PersistentState::load(MemoryStorage::new()).unwrap();
+        for next_edition in 2..2 + SLOTS_PER_SECTOR as u64 {
+            set_edition(&mut state, next_edition);
+        }
+        let last = 1 + SLOTS_PER_SECTOR as u64;
+        // Power was lost after the other sector was erased, but before the record was written
+        state.storage.erase_sector(1).unwrap();
+        state = reload(state);
+        assert_eq!(state.get().next_edition, last);
+        set_edition(&mut state, last + 1);
+        assert_eq!(reload(state).get().next_edition, last + 1);
+    }
+
+    #[test]
+    fn latest_sequence_wins_across_sectors() {
+        let mut state = PersistentState::load(MemoryStorage::new()).unwrap();
+        set_edition(&mut state, 2);
+        // A stale record left in the other sector, as after a switch back to the first
+        slot(&mut state, 1, 0).copy_from_slice(&encode_record(0, &State { next_edition: 99 }));
+        assert_eq!(reload(state).get().next_edition, 2);
+    }
+
+    struct FailingStorage(MemoryStorage);
+
+    impl Storage for FailingStorage {
+        fn sector_size(&self) -> usize {
+            self.0.sector_size()
+        }
+
+        fn read(&self, _offset: usize, _buf: &mut [u8]) -> Result<(), StorageError> {
+            Err(StorageError)
+        }
+
+        fn erase_sector(&mut self, sector: usize) -> Result<(), StorageError> {
+            self.0.erase_sector(sector)
+        }
+
+        fn program(&mut self, offset: usize, data: &[u8]) -> Result<(), StorageError> {
+            self.0.program(offset, data)
+        }
+    }
+
+    #[test]
+    fn read_error_is_not_erased_storage() {
+        let mut state = PersistentState::load(MemoryStorage::new()).unwrap();
+        set_edition(&mut state, 2);
+        let storage = FailingStorage(state.storage);
+        assert!(PersistentState::load(storage).is_err());
+    }
+}
@@ -0,0 +1,171 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+// Limits on how many pieces this device may sign, enforced here rather than in the untrusted
+// `assistant` so that a compromised `assistant` cannot flood the market.
+//
+// The lifetime quota is expressed in terms of the persistent edition counter, so it survives
+// reboots. The rate limit is a sliding window over the times of the most recent signatures.
+
+use alloc::collections::VecDeque;
+
+use banscii_artist_interface_types::ArtistError;
+
+pub(crate) const MAX_SIGNATURES_PER_WINDOW: u32 = 10;
+pub(crate) const WINDOW_MS: u64 = 60_000;
+pub(crate) const LIFETIME_QUOTA: u64 = 1_000;
+
+pub(crate) struct Policy {
+    // Times, in milliseconds, of the signatures issued within the last window, oldest first
+    recent: VecDeque<u64>,
+}
+
+impl Policy {
+    pub(crate) fn new() -> Self {
+        Self {
+            recent: VecDeque::with_capacity(MAX_SIGNATURES_PER_WINDOW as usize),
+        }
+    }
+
+    // Checks whether another signature may be issued, and if so, records it
+    pub(crate) fn claim(&mut self, now_ms: u64, next_edition: u64) -> Result<(), ArtistError> {
+        if lifetime_remaining(next_edition) == 0 {
+            return Err(ArtistError::QuotaExceeded);
+        }
+        if self.window_remaining(now_ms) == 0 {
+            return Err(ArtistError::RateLimited {
+                retry_after_ms: self.window_resets_in_ms(now_ms),
+            });
+        }
+        self.recent.push_back(now_ms);
+        Ok(())
+    }
+
+    pub(crate) fn window_remaining(&mut self, now_ms: u64) -> u32 {
+        while let Some(oldest) = self.recent.front() {
+            if now_ms.saturating_sub(*oldest) < WINDOW_MS {
+                break;
+            }
+            self.recent.pop_front();
+        }
+        MAX_SIGNATURES_PER_WINDOW - self.recent.len() as u32
+    }
+
+    // Time until the oldest signature in the window expires
+    pub(crate) fn window_resets_in_ms(&self, now_ms: u64) -> u64 {
+        self.recent.front().map_or(0, |oldest| {
+            oldest.saturating_add(WINDOW_MS).saturating_sub(now_ms)
+        })
+    }
+}
+
+pub(crate) fn lifetime_remaining(next_edition: u64) -> u64 {
+    (LIFETIME_QUOTA + 1).saturating_sub(next_edition)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill_window(policy: &mut Policy, now_ms: u64) {
+        for _ in 0..MAX_SIGNATURES_PER_WINDOW {
+            policy.claim(now_ms, 1).unwrap();
+        }
+    }
+
+    #[test]
+    fn window_limit() {
+        let mut policy = Policy::new();
+        for i in 0..MAX_SIGNATURES_PER_WINDOW {
+            assert_eq!(policy.window_remaining(0), MAX_SIGNATURES_PER_WINDOW - i);
+            policy.claim(0, 1).unwrap();
+        }
+        assert_eq!(policy.window_remaining(0), 0);
+        assert_eq!(
+            policy.claim(0, 1),
+            Err(ArtistError::RateLimited {
+                retry_after_ms: WINDOW_MS
+            })
+        );
+    }
+
+    #[test]
+    fn window_boundary() {
+        let mut policy = Policy::new();
+        fill_window(&mut policy, 1_000);
+        assert_eq!(
+            policy.claim(1_000 + WINDOW_MS - 1, 1),
+            Err(ArtistError::RateLimited { retry_after_ms: 1 })
+        );
+        // The oldest signatures leave the window exactly `WINDOW_MS` after they were issued
+        assert_eq!(
+            policy.window_remaining(1_000 + WINDOW_MS),
+            MAX_SIGNATURES_PER_WINDOW
+        );
+        policy.claim(1_000 + WINDOW_MS, 1).unwrap();
+    }
+
+    #[test]
+    fn window_slides() {
+        let mut policy = Policy::new();
+        for i in 0..u64::from(MAX_SIGNATURES_PER_WINDOW) {
+            policy.claim(i * 1_000, 1).unwrap();
+        }
+        let now_ms = WINDOW_MS + 500;
+        // Only the first signature has left the window, and the second leaves it next
+        assert_eq!(policy.window_remaining(now_ms), 1);
+        assert_eq!(policy.window_resets_in_ms(now_ms), 500);
+        policy.claim(now_ms, 1).unwrap();
+        assert_eq!(
+            policy.claim(now_ms, 1),
+            Err(ArtistError::RateLimited {
+                retry_after_ms: 500
+            })
+        );
+    }
+
+    #[test]
+    fn rejected_claims_are_not_recorded() {
+        let mut policy = Policy::new();
+        fill_window(&mut policy, 0);
+        for now_ms in [1, 2, 3] {
+            assert!(policy.claim(now_ms, 1).is_err());
+        }
+        policy.claim(WINDOW_MS, 1).unwrap();
+    }
+
+    #[test]
+    fn idle_window_resets_immediately() {
+        assert_eq!(Policy::new().window_resets_in_ms(0), 0);
+    }
+
+    #[test]
+    fn lifetime_quota() {
+        assert_eq!(lifetime_remaining(1), LIFETIME_QUOTA);
+        assert_eq!(lifetime_remaining(LIFETIME_QUOTA), 1);
+        assert_eq!(lifetime_remaining(LIFETIME_QUOTA + 1), 0);
+        assert_eq!(lifetime_remaining(u64::MAX), 0);
+
+        let mut policy = Policy::new();
+        policy.claim(0, LIFETIME_QUOTA).unwrap();
+        assert_eq!(
+            policy.claim(0, LIFETIME_QUOTA + 1),
+            Err(ArtistError::QuotaExceeded)
+        );
+        // The refusal does not use up the window
+        assert_eq!(policy.window_remaining(0), MAX_SIGNATURES_PER_WINDOW - 1);
+    }
+
+    #[test]
+    fn quota_before_rate_limit() {
+        let mut policy = Policy::new();
+        fill_window(&mut policy, 0);
+        assert_eq!(
+            policy.claim(0, LIFETIME_QUOTA + 1),
+            Err(ArtistError::QuotaExceeded)
+        );
+    }
+}
@@ -0,0 +1,46 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+use alloc::vec::Vec;
+
+use sha2::{Digest, Sha256};
+
+use banscii_artist_interface_types::{ColorMode, Provenance};
+
+// Hashes a masterpiece's pixel data and colours as they arrive, a chunk at a time
+#[derive(Default)]
+pub(crate) struct MasterpieceHasher {
+    pixel_data: Sha256,
+    colors: Sha256,
+}
+
+impl MasterpieceHasher {
+    pub(crate) fn update(&mut self, pixel_data: &[u8], colors: &[u8]) {
+        self.pixel_data.update(pixel_data);
+        self.colors.update(colors);
+    }
+
+    // The bytes that are actually signed
+    pub(crate) fn signed_data(
+        self,
+        height: usize,
+        width: usize,
+        subject: &str,
+        edition: u64,
+        color_mode: ColorMode,
+    ) -> Vec<u8> {
+        let provenance = Provenance {
+            height,
+            width,
+            subject,
+            edition,
+            pixel_hash: self.pixel_data.finalize().into(),
+            color_mode,
+            color_hash: self.colors.finalize().into(),
+        };
+        postcard::to_allocvec(&provenance).unwrap()
+    }
+}
@@ -6,22 +6,367 @@
 
 #![no_std]
 
+use core::fmt;
+
 use serde::{Deserialize, Serialize};
 
+// Drafts and masterpieces may be larger than the shared regions, so completing or verifying a
+// piece takes several requests: one to begin, then one for each chunk of the draft or masterpiece,
+// and one to finish. The artist hashes the masterpiece as it goes, and signs or verifies the hashes
+// at the end. Beginning abandons any completion or verification already in progress, as does any
+// error along the way.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Request {
+    BeginComplete(BeginCompleteRequest),
+    CompleteChunk(CompleteChunkRequest),
+    FinishComplete,
+    BeginVerify(BeginVerifyRequest),
+    VerifyChunk(VerifyChunkRequest),
+    FinishVerify,
+    GetPublicKey,
+    GetStatus,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Response {
+    BeginComplete(BeginCompleteResponse),
+    CompleteChunk(CompleteChunkResponse),
+    FinishComplete(FinishCompleteResponse),
+    BeginVerify,
+    VerifyChunk,
+    FinishVerify(VerifyResponse),
+    GetPublicKey(GetPublicKeyResponse),
+    GetStatus(GetStatusResponse),
+}
+
+// The artist replies to every request with a `Result<Response, ArtistError>`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArtistError {
+    // The request could not be decoded, its contents are invalid, or it does not follow on from the
+    // requests before it
+    Malformed,
+    // A range given by the request lies outside of the shared region
+    OutOfBounds,
+    // The response would not fit in the shared region, or the draft is too wide
+    TooLarge,
+    RateLimited { retry_after_ms: u64 },
+    QuotaExceeded,
+    // Persistent state could not be updated
+    Storage,
+}
+
+impl fmt::Display for ArtistError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Malformed => write!(f, "malformed request"),
+            Self::OutOfBounds => write!(f, "request refers to memory out of bounds"),
+            Self::TooLarge => write!(f, "piece too large"),
+            Self::RateLimited { retry_after_ms } => write!(
+                f,
+                "rate limit reached, try again in {}s",
+                retry_after_ms.div_ceil(1000)
+            ),
+            Self::QuotaExceeded => write!(f, "lifetime quota exhausted"),
+            Self::Storage => write!(f, "failed to update persistent state"),
+        }
+    }
+}
+
+// `height` and `width` are those of the draft, which must not be empty, and must be at most
+// `MAX_DRAFT_HEIGHT` rows of at most `MAX_DRAFT_WIDTH` pixels
 #[derive(Debug, Serialize, Deserialize)]
-pub struct Request {
+pub struct BeginCompleteRequest {
     pub height: usize,
     pub width: usize,
-    pub draft_start: usize,
-    pub draft_size: usize,
+    pub subject_start: usize,
+    pub subject_size: usize,
+    pub palette: Palette,
+    pub dither: Dither,
+    pub render_mode: RenderMode,
+    pub color_mode: ColorMode,
 }
 
+pub const MAX_DRAFT_WIDTH: usize = 1024;
+
+pub const MAX_DRAFT_HEIGHT: usize = 65536;
+
+// The edition is claimed as soon as a completion begins, so it is skipped if the completion is
+// abandoned
 #[derive(Debug, Serialize, Deserialize)]
-pub struct Response {
+pub struct BeginCompleteResponse {
     pub height: usize,
     pub width: usize,
+    pub edition: u64,
+}
+
+// Whole rows of the draft, following those already sent. Every chunk but the last must hold a
+// whole number of rows of cells, that is, a multiple of `RenderMode::cell_height` rows.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompleteChunkRequest {
+    pub draft_start: usize,
+    pub draft_size: usize,
+}
+
+// The rows of the masterpiece that the chunk completes. Their pixel data and colours take at most
+// 4 and `ColorMode::cell_size` bytes per cell respectively, which the chunk must leave room for in
+// the shared region.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompleteChunkResponse {
     pub masterpiece_start: usize,
     pub masterpiece_size: usize,
+    pub colors_start: usize,
+    pub colors_size: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FinishCompleteResponse {
+    pub signature_start: usize,
+    pub signature_size: usize,
+    pub signature_algorithm: SignatureAlgorithm,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BeginVerifyRequest {
+    pub height: usize,
+    pub width: usize,
+    pub subject_start: usize,
+    pub subject_size: usize,
+    pub edition: u64,
+    pub color_mode: ColorMode,
     pub signature_start: usize,
     pub signature_size: usize,
+    pub signature_algorithm: SignatureAlgorithm,
+}
+
+// The next of the masterpiece's pixel data, which must be split between chunks at char boundaries,
+// and the next of its colours
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerifyChunkRequest {
+    pub masterpiece_start: usize,
+    pub masterpiece_size: usize,
+    pub colors_start: usize,
+    pub colors_size: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerifyResponse {
+    pub valid: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetPublicKeyResponse {
+    pub public_key_start: usize,
+    pub public_key_size: usize,
+    pub signature_algorithm: SignatureAlgorithm,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetStatusResponse {
+    pub window_remaining: u32,
+    pub window_limit: u32,
+    pub window_ms: u64,
+    pub window_resets_in_ms: u64,
+    pub lifetime_remaining: u64,
+    pub lifetime_quota: u64,
+}
+
+// The characters with which a masterpiece is drawn in `RenderMode::Palette`, ordered from blank to
+// fully inked pixels. Masterpieces are UTF-8 text, `width` characters per row.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Palette {
+    BuiltIn(BuiltInPalette),
+    // A UTF-8 string in the shared region, of between `MIN_CUSTOM_PALETTE_LEN` and
+    // `MAX_CUSTOM_PALETTE_LEN` non-control characters
+    Custom { start: usize, size: usize },
+}
+
+pub const MIN_CUSTOM_PALETTE_LEN: usize = 2;
+pub const MAX_CUSTOM_PALETTE_LEN: usize = 256;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BuiltInPalette {
+    // "@%#x+=:-. "
+    Short,
+    // Paul Bourke's 70 levels, from "$@B%8&WM#*" to ":,\"^`'. "
+    Long,
+    // "█▓▒░ "
+    Blocks,
+    // `Short` reversed, so that ink is drawn with the densest characters
+    Inverted,
+}
+
+impl BuiltInPalette {
+    pub const ALL: &'static [Self] = &[Self::Short, Self::Long, Self::Blocks, Self::Inverted];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Short => "short",
+            Self::Long => "long",
+            Self::Blocks => "blocks",
+            Self::Inverted => "inverted",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|palette| palette.name() == name)
+    }
+}
+
+// How grey levels between those of the palette's characters are approximated. All are
+// deterministic, so the same request always yields the same masterpiece.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Dither {
+    // Each pixel gets the nearest character
+    None,
+    // Error diffusion
+    FloydSteinberg,
+    // Ordered, with a 4x4 Bayer matrix
+    Bayer,
+}
+
+impl Dither {
+    pub const ALL: &'static [Self] = &[Self::None, Self::FloydSteinberg, Self::Bayer];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::FloydSteinberg => "floyd-steinberg",
+            Self::Bayer => "bayer",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|dither| dither.name() == name)
+    }
+}
+
+// How many draft pixels each character of a masterpiece stands for, and how they are drawn. The
+// masterpiece has a character for every cell of `cell_width` by `cell_height` pixels, of which those
+// along its right and bottom edges may be partly beyond the draft.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RenderMode {
+    // A character from the palette per pixel
+    Palette,
+    // 2x2 pixels per quadrant block character, from "▘" to "█"
+    Quadrants,
+    // 2x4 pixels per Braille pattern, from "⠁" to "⣿"
+    Braille,
+}
+
+impl RenderMode {
+    pub const ALL: &'static [Self] = &[Self::Palette, Self::Quadrants, Self::Braille];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Palette => "palette",
+            Self::Quadrants => "quadrants",
+            Self::Braille => "braille",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|mode| mode.name() == name)
+    }
+
+    pub const fn cell_width(self) -> usize {
+        match self {
+            Self::Palette => 1,
+            Self::Quadrants | Self::Braille => 2,
+        }
+    }
+
+    pub const fn cell_height(self) -> usize {
+        match self {
+            Self::Palette => 1,
+            Self::Quadrants => 2,
+            Self::Braille => 4,
+        }
+    }
+}
+
+// Whether, and with how many colours, each cell of a masterpiece is given a foreground and a
+// background colour. Colours travel separately from the pixel data, as `cell_size` bytes per cell,
+// in the same order: the foreground and then the background, each either an index into the
+// terminal's palette or, for `TrueColor`, its red, green and blue components.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorMode {
+    Off,
+    // The 16 standard colours, indexed as in SGR 30-37 followed by SGR 90-97
+    Ansi16,
+    // xterm's 256 colours
+    Ansi256,
+    // 24 bits per colour
+    TrueColor,
+}
+
+impl ColorMode {
+    pub const ALL: &'static [Self] = &[Self::Off, Self::Ansi16, Self::Ansi256, Self::TrueColor];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Ansi16 => "on",
+            Self::Ansi256 => "256",
+            Self::TrueColor => "truecolor",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|mode| mode.name() == name)
+    }
+
+    pub const fn cell_size(self) -> usize {
+        match self {
+            Self::Off => 0,
+            Self::Ansi16 | Self::Ansi256 => 2,
+            Self::TrueColor => 6,
+        }
+    }
+}
+
+// What a masterpiece's signature covers, in its postcard encoding. `pixel_hash` is the SHA-256 of
+// the masterpiece's pixel data, and `color_hash` that of its colours, which are empty if
+// `color_mode` is `Off`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Provenance<'a> {
+    pub height: usize,
+    pub width: usize,
+    pub subject: &'a str,
+    pub edition: u64,
+    pub pixel_hash: [u8; 32],
+    pub color_mode: ColorMode,
+    pub color_hash: [u8; 32],
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SignatureAlgorithm {
+    RsaPkcs1v15Sha256,
+    Ed25519,
+    EcdsaP256Sha256,
+}
+
+impl SignatureAlgorithm {
+    pub const ALL: &'static [Self] = &[
+        Self::RsaPkcs1v15Sha256,
+        Self::Ed25519,
+        Self::EcdsaP256Sha256,
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::RsaPkcs1v15Sha256 => "rsa-pkcs1v15-sha256",
+            Self::Ed25519 => "ed25519",
+            Self::EcdsaP256Sha256 => "ecdsa-p256-sha256",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|alg| alg.name() == name)
+    }
 }
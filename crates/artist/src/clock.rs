@@ -0,0 +1,19 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+// The Microkit kernel configuration exports the Arm generic timer's physical counter to user level
+
+use core::arch::asm;
+
+pub(crate) fn now_ms() -> u64 {
+    let count: u64;
+    let freq: u64;
+    unsafe {
+        asm!("isb", "mrs {}, cntpct_el0", out(reg) count, options(nomem, nostack));
+        asm!("mrs {}, cntfrq_el0", out(reg) freq, options(nomem, nostack));
+    }
+    (u128::from(count) * 1000 / u128::from(freq)) as u64
+}
@@ -0,0 +1,100 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+// Minimal driver for CFI NOR flash using the Intel command set, as emulated by QEMU for the `virt`
+// machine's pflash banks. Each bank is a pair of 16-bit devices side by side, so commands and
+// status bits are replicated into both halves of each 32-bit word.
+
+use core::ptr::{self, NonNull};
+
+use banscii_artist_core::{Storage, StorageError};
+
+const CMD_READ_ARRAY: u32 = 0x00ff_00ff;
+const CMD_CLEAR_STATUS: u32 = 0x0050_0050;
+const CMD_PROGRAM: u32 = 0x0040_0040;
+const CMD_BLOCK_ERASE: u32 = 0x0020_0020;
+const CMD_CONFIRM: u32 = 0x00d0_00d0;
+
+const STATUS_READY: u32 = 0x0080_0080;
+const STATUS_ERROR: u32 = 0x003a_003a;
+
+pub(crate) struct Flash {
+    words: NonNull<[u32]>,
+    sector_size: usize,
+}
+
+impl Flash {
+    /// # Safety
+    ///
+    /// `words` must be an uncached mapping of the flash bank that nothing else accesses.
+    pub(crate) unsafe fn new(words: NonNull<[u32]>, sector_size: usize) -> Self {
+        Self { words, sector_size }
+    }
+
+    fn word_ptr(&self, offset: usize) -> *mut u32 {
+        assert!(offset % 4 == 0 && offset / 4 < self.words.len());
+        unsafe { self.words.cast::<u32>().as_ptr().add(offset / 4) }
+    }
+
+    fn read_word(&self, offset: usize) -> u32 {
+        unsafe { ptr::read_volatile(self.word_ptr(offset)) }
+    }
+
+    fn write_word(&mut self, offset: usize, value: u32) {
+        unsafe { ptr::write_volatile(self.word_ptr(offset), value) }
+    }
+
+    // In status mode, reads from anywhere in the bank return the status register
+    fn wait_until_ready(&mut self, offset: usize) -> Result<(), StorageError> {
+        let status = loop {
+            let status = self.read_word(offset);
+            if status & STATUS_READY == STATUS_READY {
+                break status;
+            }
+        };
+        if status & STATUS_ERROR != 0 {
+            self.write_word(offset, CMD_CLEAR_STATUS);
+        }
+        self.write_word(offset, CMD_READ_ARRAY);
+        if status & STATUS_ERROR != 0 {
+            return Err(StorageError);
+        }
+        Ok(())
+    }
+}
+
+impl Storage for Flash {
+    fn sector_size(&self) -> usize {
+        self.sector_size
+    }
+
+    fn read(&self, offset: usize, buf: &mut [u8]) -> Result<(), StorageError> {
+        for (i, b) in buf.iter_mut().enumerate() {
+            let offset = offset + i;
+            let word = self.read_word(offset & !3);
+            *b = word.to_le_bytes()[offset & 3];
+        }
+        Ok(())
+    }
+
+    fn erase_sector(&mut self, sector: usize) -> Result<(), StorageError> {
+        let offset = sector * self.sector_size;
+        self.write_word(offset, CMD_BLOCK_ERASE);
+        self.write_word(offset, CMD_CONFIRM);
+        self.wait_until_ready(offset)
+    }
+
+    fn program(&mut self, offset: usize, data: &[u8]) -> Result<(), StorageError> {
+        assert!(offset % 4 == 0 && data.len() % 4 == 0);
+        for (i, chunk) in data.chunks_exact(4).enumerate() {
+            let offset = offset + i * 4;
+            self.write_word(offset, CMD_PROGRAM);
+            self.write_word(offset, u32::from_le_bytes(chunk.try_into().unwrap()));
+            self.wait_until_ready(offset)?;
+        }
+        Ok(())
+    }
+}
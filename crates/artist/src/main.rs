@@ -10,6 +10,7 @@
 extern crate alloc;
 
 use alloc::vec;
+use alloc::vec::Vec;
 
 use sel4_externally_shared::{
     access::{ReadOnly, ReadWrite},
@@ -20,18 +21,22 @@ use sel4_microkit::{
 };
 use sel4_microkit_message::MessageInfoExt as _;
 
+use banscii_artist_core::Artist;
 use banscii_artist_interface_types::*;
 
-mod artistic_secrets;
-mod cryptographic_secrets;
+mod clock;
+mod flash;
 
-use artistic_secrets::Masterpiece;
+use flash::Flash;
 
 const ASSISTANT: Channel = Channel::new(0);
 
 const REGION_SIZE: usize = 0x4_000;
 
-#[protection_domain(heap_size = 0x10000)]
+const PERSISTENT_STATE_REGION_SIZE: usize = 0x80_000;
+const PERSISTENT_STATE_SECTOR_SIZE: usize = 0x40_000;
+
+#[protection_domain(heap_size = 0x20000)]
 fn init() -> HandlerImpl {
     let region_in = unsafe {
         ExternallySharedRef::new(memory_region_symbol!(region_in_start: *mut [u8], n = REGION_SIZE))
@@ -43,15 +48,35 @@ fn init() -> HandlerImpl {
         )
     };
 
+    let flash = unsafe {
+        Flash::new(
+            memory_region_symbol!(
+                persistent_state_start: *mut [u32],
+                n = PERSISTENT_STATE_REGION_SIZE / 4
+            ),
+            PERSISTENT_STATE_SECTOR_SIZE,
+        )
+    };
+
+    let artist = Artist::new(flash).unwrap_or_else(|err| panic!("{err}"));
+
     HandlerImpl {
         region_in,
         region_out,
+        region_in_buf: vec![0; REGION_SIZE],
+        region_out_buf: vec![0; REGION_SIZE],
+        artist,
     }
 }
 
 struct HandlerImpl {
     region_in: ExternallySharedRef<'static, [u8], ReadOnly>,
     region_out: ExternallySharedRef<'static, [u8], ReadWrite>,
+    // Private copies of the shared regions, which the assistant cannot modify while `artist` is
+    // working on them
+    region_in_buf: Vec<u8>,
+    region_out_buf: Vec<u8>,
+    artist: Artist<Flash>,
 }
 
 impl Handler for HandlerImpl {
@@ -63,57 +88,34 @@ impl Handler for HandlerImpl {
         msg_info: MessageInfo,
     ) -> Result<MessageInfo, Self::Error> {
         Ok(match channel {
-            ASSISTANT => match msg_info.recv_using_postcard::<Request>() {
-                Ok(req) => {
-                    let draft_height = req.height;
-                    let draft_width = req.width;
-                    let draft = {
-                        let mut buf = vec![0; req.draft_size];
-                        self.region_in
-                            .as_ptr()
-                            .index(req.draft_start..req.draft_start + req.draft_size)
-                            .copy_into_slice(&mut buf);
-                        buf
-                    };
-
-                    let masterpiece = Masterpiece::complete(draft_height, draft_width, &draft);
-
-                    let masterpiece_start = 0;
-                    let masterpiece_size = masterpiece.pixel_data.len();
-                    let masterpiece_end = masterpiece_start + masterpiece_size;
-
-                    self.region_out
-                        .as_mut_ptr()
-                        .index(masterpiece_start..masterpiece_end)
-                        .copy_from_slice(&masterpiece.pixel_data);
-
-                    let signature = cryptographic_secrets::sign(&masterpiece.pixel_data);
-                    let signature = signature.as_ref();
-
-                    let signature_start = masterpiece_end;
-                    let signature_size = signature.len();
-                    let signature_end = signature_start + signature_size;
-
-                    self.region_out
-                        .as_mut_ptr()
-                        .index(signature_start..signature_end)
-                        .copy_from_slice(signature);
-
-                    MessageInfo::send_using_postcard(Response {
-                        height: masterpiece.height,
-                        width: masterpiece.width,
-                        masterpiece_start,
-                        masterpiece_size,
-                        signature_start,
-                        signature_size,
-                    })
-                    .unwrap()
-                }
-                Err(_) => MessageInfo::send_unspecified_error(),
-            },
+            ASSISTANT => {
+                let resp = match msg_info.recv_using_postcard::<Request>() {
+                    Ok(req) => self.handle_request(req),
+                    Err(_) => Err(ArtistError::Malformed),
+                };
+                MessageInfo::send_using_postcard(resp).unwrap()
+            }
             _ => {
                 unreachable!()
             }
         })
     }
 }
+
+impl HandlerImpl {
+    fn handle_request(&mut self, req: Request) -> Result<Response, ArtistError> {
+        self.region_in
+            .as_ptr()
+            .copy_into_slice(&mut self.region_in_buf);
+        let resp = self.artist.handle_request(
+            req,
+            clock::now_ms(),
+            &self.region_in_buf,
+            &mut self.region_out_buf,
+        )?;
+        self.region_out
+            .as_mut_ptr()
+            .copy_from_slice(&self.region_out_buf);
+        Ok(resp)
+    }
+}
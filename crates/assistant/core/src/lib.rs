@@ -8,60 +8,175 @@
 
 extern crate alloc;
 
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
 use alloc::vec;
 use alloc::vec::Vec;
+use core::iter;
+use core::mem;
 
 use ab_glyph::{point, Font, FontRef, Glyph, Point, PxScale, ScaleFont};
 use num_traits::Float;
 
+mod line_editor;
+mod shell;
+mod typeface;
+
+pub use line_editor::LineEditor;
+pub use shell::{ArtistChannel, Assistant, RegionIn, RegionOut, HEAP_SIZE};
+pub use typeface::Typeface;
+
 pub struct Draft {
     pub width: usize,
     pub height: usize,
     pub pixel_data: Vec<u8>,
+    // Characters of the subject that no font in the chain could draw, in order of first appearance
+    pub missing: Vec<char>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DraftOptions {
+    pub typeface: Typeface,
+    // Tried in order for characters that `typeface` lacks
+    pub fallbacks: Vec<Typeface>,
+    // Font pixel height, i.e. the number of rows spanned by a line of text
+    pub height: f32,
+    // If set, `height` is ignored, and the largest height at which the widest line of the subject
+    // (padding included) fits in this many columns is used instead
+    pub columns: Option<usize>,
+    // Horizontal scale relative to vertical, to counter the aspect ratio of the cells of the
+    // terminal on which the art will be displayed
+    pub aspect_ratio: f32,
+    // Number of blank rows and columns on each side of the subject
+    pub padding: usize,
+    // If set, the subject is wrapped onto multiple lines, at word boundaries where possible, so
+    // that the draft (padding included) is at most this many columns wide
+    pub max_width: Option<usize>,
+    // If set, the subject is drawn smaller as need be for the draft to take at most this many
+    // bytes. It is drawn no smaller than a pixel high, though, at which the draft may still take
+    // more, as it does if its padding alone does.
+    pub max_size: Option<usize>,
+}
+
+impl DraftOptions {
+    // Gets 80 chars across (fits most terminals) with the default typeface
+    pub const DEFAULT_HEIGHT: f32 = 12.4;
+
+    // Terminal character cells are typically about twice as tall as they are wide
+    pub const DEFAULT_ASPECT_RATIO: f32 = 2.0;
+}
+
+impl Default for DraftOptions {
+    fn default() -> Self {
+        Self {
+            typeface: Typeface::default(),
+            fallbacks: Vec::new(),
+            height: Self::DEFAULT_HEIGHT,
+            columns: None,
+            aspect_ratio: Self::DEFAULT_ASPECT_RATIO,
+            padding: 0,
+            max_width: None,
+            max_size: None,
+        }
+    }
 }
 
 impl Draft {
-    // Derived from:
-    // https://github.com/alexheretic/ab-glyph/blob/main/dev/examples/ascii.rs
     pub fn new(subject: &str) -> Self {
-        let font_data = include_bytes!("../assets/fonts/rock-salt/RockSalt-Regular.ttf");
-        let font = FontRef::try_from_slice(font_data).unwrap();
+        Self::with_options(subject, &DraftOptions::default())
+    }
 
-        // Desired font pixel height
-        let height: f32 = 12.4; // to get 80 chars across (fits most terminals); adjust as desired
-        let px_height = height.ceil() as usize;
+    // Derived from:
+    // https://github.com/alexheretic/ab-glyph/blob/main/dev/examples/ascii.rs
+    pub fn with_options(subject: &str, options: &DraftOptions) -> Self {
+        let fonts = iter::once(&options.typeface)
+            .chain(&options.fallbacks)
+            .map(|typeface| FontRef::try_from_slice(typeface.font_data()).unwrap())
+            .collect::<Vec<_>>();
 
-        // 2x scale in x direction to counter the aspect ratio of monospace characters.
-        let scale = PxScale {
-            x: height * 2.0,
+        let scale = |height: f32| PxScale {
+            x: height * options.aspect_ratio,
             y: height,
         };
 
-        let scaled_font = font.into_scaled(scale);
+        let padding = options.padding;
+        let inner_columns = options
+            .columns
+            .map(|columns| columns.saturating_sub(2 * padding));
+
+        // Desired font pixel height
+        let mut height = match inner_columns {
+            Some(columns) => fit_height(&fonts, scale, subject, columns as f32),
+            None => options.height,
+        };
+
+        // Shrinks the subject until the draft fits in `max_size` bytes, if given, or is as small as
+        // it gets
+        let (scaled_fonts, lines, line_advance, inner_width, inner_height) = loop {
+            let scaled_fonts = fonts
+                .iter()
+                .map(|font| font.as_scaled(scale(height)))
+                .collect::<Vec<_>>();
+
+            let lines = wrap(
+                &scaled_fonts,
+                subject,
+                options
+                    .max_width
+                    .map(|max_width| max_width.saturating_sub(2 * padding) as f32),
+            );
+
+            let line_advance = scaled_fonts[0].height() + scaled_fonts[0].line_gap();
+            let inner_height =
+                (height + line_advance * lines.len().saturating_sub(1) as f32).ceil() as usize;
+
+            // Find the most visually pleasing width to display
+            let inner_width = lines
+                .iter()
+                .map(|line| text_width(&scaled_fonts, line))
+                .fold(0.0, f32::max)
+                .ceil() as usize;
+
+            let size = (inner_width + 2 * padding) * (inner_height + 2 * padding);
+            match options.max_size {
+                Some(max_size) if size > max_size && height > 1.0 => {
+                    height *= 0.9;
+                }
+                _ => break (scaled_fonts, lines, line_advance, inner_width, inner_height),
+            }
+        };
 
         let mut glyphs = Vec::new();
-        layout(&scaled_font, point(0.0, 0.0), subject, &mut glyphs);
+        let mut missing = Vec::new();
+        for (i, line) in lines.iter().enumerate() {
+            layout(
+                &scaled_fonts,
+                point(0.0, line_advance * i as f32),
+                line,
+                &mut glyphs,
+                &mut missing,
+            );
+        }
 
-        // Find the most visually pleasing width to display
-        let px_width = glyphs
-            .iter()
-            .rev()
-            .map(|g| g.position.x + scaled_font.h_advance(g.id))
-            .next()
-            .unwrap_or(0.0)
-            .ceil() as usize;
+        let px_width = inner_width + 2 * padding;
+        let px_height = inner_height + 2 * padding;
 
         // Rasterize to greyscale
         let mut pixel_data = vec![0; px_width * px_height];
         for g in glyphs {
-            if let Some(og) = scaled_font.outline_glyph(g) {
+            if let Some(og) = scaled_fonts[g.font].outline_glyph(g.glyph) {
                 let bounds = og.px_bounds();
                 og.draw(|x, y, v| {
                     let x = x as f32 + bounds.min.x;
                     let y = y as f32 + bounds.min.y;
-                    // There's still a possibility that the glyph clips the boundaries of the bitmap
-                    if x >= 0.0 && (x as usize) < px_width && y >= 0.0 && (y as usize) < px_height {
-                        pixel_data[(x as usize) + (y as usize) * px_width] =
+                    // There's still a possibility that the glyph clips the boundaries of the text
+                    if x >= 0.0
+                        && (x as usize) < inner_width
+                        && y >= 0.0
+                        && (y as usize) < inner_height
+                    {
+                        pixel_data[(x as usize + padding) + (y as usize + padding) * px_width] =
                             (v * 255.0 + 0.5) as u8;
                     }
                 })
@@ -72,27 +187,154 @@ impl Draft {
             width: px_width,
             height: px_height,
             pixel_data,
+            missing,
         }
     }
 }
 
-pub fn layout<F, SF>(font: SF, position: Point, text: &str, target: &mut Vec<Glyph>)
+// Finds the largest height at which each line of `text` is at most `columns` wide. Widths are
+// proportional to the scale, so one measurement gives a close estimate, but for rounding error,
+// which is settled by bisecting between 0 and the estimate.
+fn fit_height(fonts: &[FontRef], scale: impl Fn(f32) -> PxScale, text: &str, columns: f32) -> f32 {
+    // Enough to narrow the range to the precision of an f32
+    const BISECTION_STEPS: usize = 24;
+
+    let widest = |height: f32| {
+        let scaled_fonts = fonts
+            .iter()
+            .map(|font| font.as_scaled(scale(height)))
+            .collect::<Vec<_>>();
+        text.split('\n')
+            .map(|line| text_width(&scaled_fonts, line))
+            .fold(0.0, f32::max)
+    };
+    let reference = DraftOptions::DEFAULT_HEIGHT;
+    let width = widest(reference);
+    if width <= 0.0 {
+        return reference;
+    }
+    let estimate = reference * columns / width;
+    if widest(estimate) <= columns {
+        return estimate;
+    }
+    // `low` always fits, and `high` never does
+    let (mut low, mut high) = (0.0, estimate);
+    for _ in 0..BISECTION_STEPS {
+        let mid = (low + high) / 2.0;
+        if widest(mid) <= columns {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+// Splits `text` into lines at '\n', and then, if `max_width` is given, greedily wraps each line at
+// spaces. Words that are too wide on their own are broken between characters. Lines that already
+// fit are left as they are.
+fn wrap<F, SF>(fonts: &[SF], text: &str, max_width: Option<f32>) -> Vec<String>
 where
     F: Font,
     SF: ScaleFont<F>,
 {
-    let mut caret = position + point(0.0, font.ascent());
-    let mut last_glyph: Option<Glyph> = None;
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let max_width = match max_width {
+            Some(max_width) if text_width(fonts, paragraph) > max_width => max_width,
+            _ => {
+                lines.push(paragraph.to_owned());
+                continue;
+            }
+        };
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            if !line.is_empty() {
+                let candidate = format!("{line} {word}");
+                if text_width(fonts, &candidate) <= max_width {
+                    line = candidate;
+                    continue;
+                }
+                lines.push(mem::take(&mut line));
+            }
+            for c in word.chars() {
+                line.push(c);
+                if text_width(fonts, &line) > max_width && line.chars().count() > 1 {
+                    line.pop();
+                    lines.push(mem::replace(&mut line, String::from(c)));
+                }
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+fn text_width<F, SF>(fonts: &[SF], text: &str) -> f32
+where
+    F: Font,
+    SF: ScaleFont<F>,
+{
+    let mut glyphs = Vec::new();
+    layout(fonts, point(0.0, 0.0), text, &mut glyphs, &mut Vec::new());
+    glyphs
+        .iter()
+        .rev()
+        .map(|g| g.glyph.position.x + fonts[g.font].h_advance(g.glyph.id))
+        .next()
+        .unwrap_or(0.0)
+}
+
+// A glyph placed by `layout`
+#[derive(Debug, Clone)]
+pub struct LaidOutGlyph {
+    // Index into the font chain of the font that the glyph is from
+    pub font: usize,
+    pub glyph: Glyph,
+}
+
+// Lays out `text` in a chain of fonts, taking each character from the first font that maps it.
+// Characters that no font maps are appended to `missing` (unless they are there already), and are
+// drawn with the first font's `.notdef` glyph. Line metrics come from the first font.
+pub fn layout<F, SF>(
+    fonts: &[SF],
+    position: Point,
+    text: &str,
+    target: &mut Vec<LaidOutGlyph>,
+    missing: &mut Vec<char>,
+) where
+    F: Font,
+    SF: ScaleFont<F>,
+{
+    let mut caret = position + point(0.0, fonts[0].ascent());
+    let mut last_glyph: Option<LaidOutGlyph> = None;
     for c in text.chars() {
+        let font_index = match fonts.iter().position(|font| font.glyph_id(c).0 != 0) {
+            Some(i) => i,
+            None => {
+                if !missing.contains(&c) {
+                    missing.push(c);
+                }
+                0
+            }
+        };
+        let font = &fonts[font_index];
         let mut glyph = font.scaled_glyph(c);
         if let Some(previous) = last_glyph.take() {
-            caret.x += font.kern(previous.id, glyph.id);
+            // Kerning is only defined between glyphs of the same font
+            if previous.font == font_index {
+                caret.x += font.kern(previous.glyph.id, glyph.id);
+            }
         }
         glyph.position = caret;
-
-        last_glyph = Some(glyph.clone());
         caret.x += font.h_advance(glyph.id);
 
-        target.push(glyph);
+        let laid_out = LaidOutGlyph {
+            font: font_index,
+            glyph,
+        };
+        last_glyph = Some(laid_out.clone());
+
+        target.push(laid_out);
     }
 }
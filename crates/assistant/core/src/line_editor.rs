@@ -0,0 +1,336 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+// A minimal line editor for an ANSI terminal on the other end of a serial line. Line endings are
+// left to the caller, which passes everything else in one char at a time.
+//
+// Each char is assumed to occupy one column. Wide and combining characters can leave the terminal's
+// cursor out of step with the editor's until the line is submitted.
+
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+use core::mem;
+
+const HISTORY_LEN: usize = 32;
+
+const BACKSPACE: char = '\x08';
+const DELETE: char = '\x7f';
+const CTRL_A: char = '\x01';
+const CTRL_E: char = '\x05';
+const CTRL_U: char = '\x15';
+const CTRL_W: char = '\x17';
+const ESCAPE: char = '\x1b';
+const BELL: char = '\x07';
+
+const CLEAR_TO_END: &str = "\x1b[K";
+
+pub struct LineEditor {
+    line: Vec<char>,
+    // Index into `line` of the char before which the next one will be inserted
+    cursor: usize,
+    escape: Escape,
+    // Most recent first
+    history: VecDeque<String>,
+    // Index into `history` of the entry being shown, if any
+    history_pos: Option<usize>,
+    // The line as it was before browsing history
+    unsubmitted: Vec<char>,
+}
+
+#[derive(Copy, Clone)]
+enum Escape {
+    None,
+    Started,
+    // Control Sequence Introducer ("ESC ["). Only the first parameter is of interest.
+    Csi {
+        param: Option<u16>,
+        param_done: bool,
+    },
+    // Single Shift Three ("ESC O"), which some terminals use for cursor keys
+    Ss3,
+}
+
+enum Key {
+    Up,
+    Down,
+    Right,
+    Left,
+    Home,
+    End,
+    Delete,
+}
+
+impl LineEditor {
+    pub fn new() -> Self {
+        Self {
+            line: Vec::new(),
+            cursor: 0,
+            escape: Escape::None,
+            history: VecDeque::new(),
+            history_pos: None,
+            unsubmitted: Vec::new(),
+        }
+    }
+
+    pub fn line(&self) -> String {
+        self.line.iter().collect()
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    // Clears the line and returns what it was. The caller is responsible for moving the terminal's
+    // cursor to the next line.
+    pub fn take_line(&mut self) -> String {
+        let line = self.line();
+        self.line.clear();
+        self.cursor = 0;
+        self.escape = Escape::None;
+        self.history_pos = None;
+        self.unsubmitted.clear();
+        line
+    }
+
+    // Empty lines and repeats of the most recent entry are not recorded
+    pub fn add_to_history(&mut self, line: &str) {
+        if line.is_empty() || self.history.front().map(String::as_str) == Some(line) {
+            return;
+        }
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_back();
+        }
+        self.history.push_front(line.into());
+    }
+
+    // Applies `c` to the line and echoes the result to `out`. If `max_len` is given, chars that
+    // would make the line longer are refused with a bell.
+    pub fn handle_char<W: Write + ?Sized>(
+        &mut self,
+        c: char,
+        max_len: Option<usize>,
+        out: &mut W,
+    ) -> fmt::Result {
+        match mem::replace(&mut self.escape, Escape::None) {
+            Escape::None => {}
+            Escape::Started => {
+                self.escape = match c {
+                    '[' => Escape::Csi {
+                        param: None,
+                        param_done: false,
+                    },
+                    'O' => Escape::Ss3,
+                    // Not a sequence that we understand, so drop it
+                    _ => Escape::None,
+                };
+                return Ok(());
+            }
+            Escape::Csi { param, param_done } => {
+                return match c {
+                    '0'..='9' if !param_done => {
+                        let digit = c as u16 - '0' as u16;
+                        self.escape = Escape::Csi {
+                            param: Some(
+                                param.unwrap_or(0).saturating_mul(10).saturating_add(digit),
+                            ),
+                            param_done,
+                        };
+                        Ok(())
+                    }
+                    // The rest of the parameter bytes, and any intermediate bytes
+                    '\x20'..='\x3f' => {
+                        self.escape = Escape::Csi {
+                            param,
+                            param_done: true,
+                        };
+                        Ok(())
+                    }
+                    'A' => self.handle_key(Key::Up, out),
+                    'B' => self.handle_key(Key::Down, out),
+                    'C' => self.handle_key(Key::Right, out),
+                    'D' => self.handle_key(Key::Left, out),
+                    'H' => self.handle_key(Key::Home, out),
+                    'F' => self.handle_key(Key::End, out),
+                    '~' => match param {
+                        Some(1 | 7) => self.handle_key(Key::Home, out),
+                        Some(4 | 8) => self.handle_key(Key::End, out),
+                        Some(3) => self.handle_key(Key::Delete, out),
+                        _ => Ok(()),
+                    },
+                    // Any other final byte ends a sequence that we ignore
+                    _ => Ok(()),
+                };
+            }
+            Escape::Ss3 => {
+                return match c {
+                    'A' => self.handle_key(Key::Up, out),
+                    'B' => self.handle_key(Key::Down, out),
+                    'C' => self.handle_key(Key::Right, out),
+                    'D' => self.handle_key(Key::Left, out),
+                    'H' => self.handle_key(Key::Home, out),
+                    'F' => self.handle_key(Key::End, out),
+                    _ => Ok(()),
+                };
+            }
+        }
+
+        match c {
+            ESCAPE => {
+                self.escape = Escape::Started;
+            }
+            BACKSPACE | DELETE => {
+                if self.cursor > 0 {
+                    self.delete_before_cursor(self.cursor - 1, out)?;
+                }
+            }
+            CTRL_U => {
+                self.delete_before_cursor(0, out)?;
+            }
+            CTRL_W => {
+                // Delete the word before the cursor, along with any spaces between the two
+                let mut start = self.cursor;
+                while start > 0 && self.line[start - 1].is_whitespace() {
+                    start -= 1;
+                }
+                while start > 0 && !self.line[start - 1].is_whitespace() {
+                    start -= 1;
+                }
+                self.delete_before_cursor(start, out)?;
+            }
+            CTRL_A => {
+                self.handle_key(Key::Home, out)?;
+            }
+            CTRL_E => {
+                self.handle_key(Key::End, out)?;
+            }
+            _ if c.is_control() => {}
+            _ => {
+                if max_len.is_some_and(|max_len| self.line.len() >= max_len) {
+                    out.write_char(BELL)?;
+                } else {
+                    self.line.insert(self.cursor, c);
+                    self.cursor += 1;
+                    out.write_char(c)?;
+                    if self.cursor < self.line.len() {
+                        self.redraw_after_cursor(out)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_key<W: Write + ?Sized>(&mut self, key: Key, out: &mut W) -> fmt::Result {
+        match key {
+            Key::Left => {
+                if self.cursor > 0 {
+                    self.cursor -= 1;
+                    move_left(1, out)?;
+                }
+            }
+            Key::Right => {
+                if self.cursor < self.line.len() {
+                    self.cursor += 1;
+                    move_right(1, out)?;
+                }
+            }
+            Key::Home => {
+                move_left(self.cursor, out)?;
+                self.cursor = 0;
+            }
+            Key::End => {
+                move_right(self.line.len() - self.cursor, out)?;
+                self.cursor = self.line.len();
+            }
+            Key::Delete => {
+                if self.cursor < self.line.len() {
+                    self.line.remove(self.cursor);
+                    self.redraw_after_cursor(out)?;
+                }
+            }
+            Key::Up => {
+                let pos = self.history_pos.map_or(0, |pos| pos + 1);
+                if pos < self.history.len() {
+                    if self.history_pos.is_none() {
+                        self.unsubmitted = self.line.clone();
+                    }
+                    self.history_pos = Some(pos);
+                    let entry = self.history[pos].chars().collect();
+                    self.replace_line(entry, out)?;
+                }
+            }
+            Key::Down => {
+                if let Some(pos) = self.history_pos {
+                    let entry = if pos == 0 {
+                        self.history_pos = None;
+                        mem::take(&mut self.unsubmitted)
+                    } else {
+                        self.history_pos = Some(pos - 1);
+                        self.history[pos - 1].chars().collect()
+                    };
+                    self.replace_line(entry, out)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Deletes the chars from `start` up to the cursor
+    fn delete_before_cursor<W: Write + ?Sized>(
+        &mut self,
+        start: usize,
+        out: &mut W,
+    ) -> fmt::Result {
+        move_left(self.cursor - start, out)?;
+        self.line.drain(start..self.cursor);
+        self.cursor = start;
+        self.redraw_after_cursor(out)
+    }
+
+    // Leaves the cursor at the end of the new line
+    fn replace_line<W: Write + ?Sized>(&mut self, line: Vec<char>, out: &mut W) -> fmt::Result {
+        move_left(self.cursor, out)?;
+        self.line = line;
+        self.cursor = self.line.len();
+        for c in &self.line {
+            out.write_char(*c)?;
+        }
+        out.write_str(CLEAR_TO_END)
+    }
+
+    // Rewrites the rest of the line, and clears whatever was left beyond its end, leaving the
+    // terminal's cursor where it was
+    fn redraw_after_cursor<W: Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        let rest = &self.line[self.cursor..];
+        for c in rest {
+            out.write_char(*c)?;
+        }
+        out.write_str(CLEAR_TO_END)?;
+        move_left(rest.len(), out)
+    }
+}
+
+impl Default for LineEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn move_left<W: Write + ?Sized>(n: usize, out: &mut W) -> fmt::Result {
+    if n > 0 {
+        write!(out, "\x1b[{}D", n)?;
+    }
+    Ok(())
+}
+
+fn move_right<W: Write + ?Sized>(n: usize, out: &mut W) -> fmt::Result {
+    if n > 0 {
+        write!(out, "\x1b[{}C", n)?;
+    }
+    Ok(())
+}
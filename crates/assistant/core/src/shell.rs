@@ -0,0 +1,977 @@
+//
+// Copyright 2023, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+// The assistant's text interface. Its surroundings are abstracted behind `serial`'s traits and
+// those below, so that it can run either as a protection domain or on the host.
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+use core::mem;
+use core::ops::RangeInclusive;
+use core::str;
+
+use embedded_hal_nb::serial;
+use sha2::{Digest, Sha256};
+
+use banscii_artist_interface_types as artist;
+use banscii_piece_format::{Piece, PieceReader, MAX_METADATA_LINES};
+
+use crate::{Draft, DraftOptions, LineEditor, Typeface};
+
+// In chars, rather than bytes
+const MAX_SUBJECT_LEN: usize = 64;
+
+// Long enough for the largest custom palette that the artist accepts
+const MAX_LINE_LEN: usize = "palette custom ".len() + artist::MAX_CUSTOM_PALETTE_LEN;
+
+// Subjects are wrapped to fit in an 80-column terminal unless the operator says otherwise
+const DEFAULT_TERMINAL_WIDTH: usize = 80;
+
+const TERMINAL_WIDTH_RANGE: RangeInclusive<usize> = 16..=256;
+
+const HEIGHT_RANGE: RangeInclusive<f32> = 1.0..=64.0;
+
+const ASPECT_RATIO_RANGE: RangeInclusive<f32> = 0.25..=4.0;
+
+const SGR_RESET: &str = "\x1b[0m";
+
+// Pieces are sent to the artist for verification in chunks, but are held in full until then
+const MAX_PIECE_SIZE: usize = 0x80_000;
+
+// Drafts are sent to the artist in chunks too, but are drawn in full first
+const MAX_DRAFT_SIZE: usize = 2 * MAX_PIECE_SIZE;
+
+// The most bytes that a char takes in UTF-8
+const MAX_CHAR_SIZE: usize = 4;
+
+// The longest escape sequence that `write_sgr` writes
+const MAX_SGR_LEN: usize = "\x1b[38;2;255;255;255;48;2;255;255;255m".len();
+
+// The longest line of a piece is a row of as many cells as drafts have pixels across, each of a
+// colour of its own
+const MAX_PASTED_LINE_LEN: usize =
+    artist::MAX_DRAFT_WIDTH * (MAX_SGR_LEN + MAX_CHAR_SIZE) + SGR_RESET.len();
+
+// A pasted piece takes up to four times its size, as `PieceReader` describes, along with the lines
+// that it holds back, and the line being pasted, whose buffer may be twice its length. A draft,
+// which is never held at the same time, takes less. The rest is for everything else.
+pub const HEAP_SIZE: usize =
+    4 * MAX_PIECE_SIZE + (MAX_METADATA_LINES + 2) * MAX_PASTED_LINE_LEN + 0x10_000;
+
+enum Palette {
+    BuiltIn(artist::BuiltInPalette),
+    Custom(String),
+}
+
+// How drafts are sized, as set by the operator
+#[derive(Debug, Copy, Clone)]
+enum Height {
+    Fixed(f32),
+    // Fill the terminal width
+    Fit,
+}
+
+// The region that the artist writes and the assistant reads
+pub trait RegionIn {
+    fn size(&self) -> usize;
+
+    fn read(&self, start: usize, buf: &mut [u8]);
+}
+
+// The region that the assistant writes and the artist reads
+pub trait RegionOut {
+    fn size(&self) -> usize;
+
+    fn write(&mut self, start: usize, data: &[u8]);
+}
+
+// The channel on which the assistant calls the artist
+pub trait ArtistChannel {
+    fn call(&mut self, req: artist::Request) -> Result<artist::Response, artist::ArtistError>;
+}
+
+pub struct Assistant<T, A, I, O> {
+    serial: T,
+    artist: A,
+    region_in: I,
+    region_out: O,
+    line_editor: LineEditor,
+    // A line of a piece being pasted for verification
+    pasted_line: String,
+    // Whether chars were dropped from `pasted_line` for making it too long
+    pasted_line_truncated: bool,
+    // The bytes so far of a UTF-8 encoded char, which may arrive across several calls to
+    // `handle_serial_input`
+    partial_char: Vec<u8>,
+    after_carriage_return: bool,
+    piece_reader: Option<PieceReader>,
+    typeface: Typeface,
+    palette: Palette,
+    dither: artist::Dither,
+    render_mode: artist::RenderMode,
+    color_mode: artist::ColorMode,
+    terminal_width: usize,
+    height: Height,
+    aspect_ratio: f32,
+}
+
+impl<T, A, I, O> Assistant<T, A, I, O>
+where
+    T: serial::Read + serial::Write,
+    A: ArtistChannel,
+    I: RegionIn,
+    O: RegionOut,
+{
+    pub fn new(serial: T, artist: A, region_in: I, region_out: O) -> Self {
+        Self {
+            serial,
+            artist,
+            region_in,
+            region_out,
+            line_editor: LineEditor::new(),
+            pasted_line: String::new(),
+            pasted_line_truncated: false,
+            partial_char: Vec::new(),
+            after_carriage_return: false,
+            piece_reader: None,
+            typeface: Typeface::default(),
+            palette: Palette::BuiltIn(artist::BuiltInPalette::Short),
+            dither: artist::Dither::None,
+            render_mode: artist::RenderMode::Palette,
+            color_mode: artist::ColorMode::Off,
+            terminal_width: DEFAULT_TERMINAL_WIDTH,
+            height: Height::Fixed(DraftOptions::DEFAULT_HEIGHT),
+            aspect_ratio: DraftOptions::DEFAULT_ASPECT_RATIO,
+        }
+    }
+
+    pub fn serial_mut(&mut self) -> &mut T {
+        &mut self.serial
+    }
+
+    pub fn start(&mut self) {
+        self.prompt();
+    }
+
+    // Consumes all input that is currently available
+    pub fn handle_serial_input(&mut self) {
+        while let Ok(b) = self.serial.read() {
+            let Some(c) = self.decode(b) else {
+                continue;
+            };
+            let after_carriage_return = mem::replace(&mut self.after_carriage_return, false);
+            if let '\n' | '\r' = c {
+                // Treat "\r\n" as a single line ending
+                if c == '\n' && after_carriage_return {
+                    continue;
+                }
+                self.after_carriage_return = c == '\r';
+                self.newline();
+                self.handle_line();
+            } else if self.piece_reader.is_some() {
+                // Pasted pieces are taken verbatim, so that the escape sequences which color them
+                // reach the piece reader, and are not subject to the limit on commands
+                if self.pasted_line.len() + c.len_utf8() > MAX_PASTED_LINE_LEN {
+                    self.pasted_line_truncated = true;
+                } else {
+                    self.pasted_line.push(c);
+                }
+                write!(self.writer(), "{}", c).unwrap();
+            } else {
+                let writer = &mut self.serial as &mut dyn serial::Write<Error = T::Error>;
+                self.line_editor
+                    .handle_char(c, Some(MAX_LINE_LEN), writer)
+                    .unwrap();
+            }
+        }
+    }
+
+    // Accumulates bytes until they encode a complete char. Invalid sequences are dropped.
+    fn decode(&mut self, b: u8) -> Option<char> {
+        self.partial_char.push(b);
+        match str::from_utf8(&self.partial_char) {
+            Ok(s) => {
+                let c = s.chars().next().unwrap();
+                self.partial_char.clear();
+                Some(c)
+            }
+            // Incomplete, so far
+            Err(err) if err.error_len().is_none() => None,
+            Err(_) => {
+                // `b` may yet begin a valid sequence of its own
+                let interrupted = self.partial_char.len() > 1;
+                self.partial_char.clear();
+                if interrupted {
+                    self.decode(b)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    fn handle_line(&mut self) {
+        let line = match self.piece_reader {
+            Some(_) => mem::take(&mut self.pasted_line),
+            None => self.line_editor.take_line(),
+        };
+        if let Some(mut piece_reader) = self.piece_reader.take() {
+            if mem::take(&mut self.pasted_line_truncated) {
+                piece_reader.fail("line too long");
+            }
+            match piece_reader.push_line(&line) {
+                Ok(None) => {
+                    self.piece_reader = Some(piece_reader);
+                    return;
+                }
+                Ok(Some(piece)) => {
+                    self.verify(&piece);
+                }
+                Err(err) => {
+                    writeln!(self.writer(), "error: {}", err).unwrap();
+                }
+            }
+        } else if !line.is_empty() {
+            self.line_editor.add_to_history(&line);
+            match line.as_str() {
+                "pubkey" => {
+                    self.print_public_key();
+                }
+                "status" => {
+                    self.print_status();
+                }
+                "font" => {
+                    self.print_typefaces();
+                }
+                line if line.starts_with("font ") => {
+                    self.set_typeface(line["font ".len()..].trim());
+                }
+                "palette" => {
+                    self.print_palettes();
+                }
+                line if line.starts_with("palette custom ") => {
+                    // Spaces are significant here
+                    self.set_custom_palette(&line["palette custom ".len()..]);
+                }
+                line if line.starts_with("palette ") => {
+                    self.set_palette(line["palette ".len()..].trim());
+                }
+                "dither" => {
+                    self.print_dithers();
+                }
+                line if line.starts_with("dither ") => {
+                    self.set_dither(line["dither ".len()..].trim());
+                }
+                "render" => {
+                    self.print_render_modes();
+                }
+                line if line.starts_with("render ") => {
+                    self.set_render_mode(line["render ".len()..].trim());
+                }
+                "color" => {
+                    self.print_color_modes();
+                }
+                line if line.starts_with("color ") => {
+                    self.set_color_mode(line["color ".len()..].trim());
+                }
+                "size" => {
+                    self.print_size();
+                }
+                line if line.starts_with("width ") => {
+                    self.set_terminal_width(line["width ".len()..].trim());
+                }
+                line if line.starts_with("height ") => {
+                    self.set_height(line["height ".len()..].trim());
+                }
+                line if line.starts_with("aspect ") => {
+                    self.set_aspect_ratio(line["aspect ".len()..].trim());
+                }
+                "verify" => {
+                    writeln!(
+                        self.writer(),
+                        "Paste a masterpiece followed by its signature, then an empty line:"
+                    )
+                    .unwrap();
+                    self.piece_reader = Some(PieceReader::new(MAX_PIECE_SIZE));
+                    return;
+                }
+                subject if subject.chars().count() > MAX_SUBJECT_LEN => {
+                    writeln!(
+                        self.writer(),
+                        "error: a subject may have at most {} characters",
+                        MAX_SUBJECT_LEN
+                    )
+                    .unwrap();
+                }
+                subject => {
+                    self.create(subject);
+                }
+            };
+        }
+        self.prompt();
+    }
+
+    fn create(&mut self, subject: &str) {
+        // The draft is drawn at the resolution of the masterpiece's cells, whose pixels are as
+        // much narrower and shorter than the terminal's cells as there are of them across and down
+        let cell_width = self.render_mode.cell_width();
+        let cell_height = self.render_mode.cell_height();
+        let draft = Draft::with_options(
+            subject,
+            &DraftOptions {
+                typeface: self.typeface,
+                // The other bundled typefaces, in case the chosen one lacks some characters
+                fallbacks: Typeface::ALL
+                    .iter()
+                    .copied()
+                    .filter(|typeface| *typeface != self.typeface)
+                    .collect(),
+                height: match self.height {
+                    Height::Fixed(height) => height,
+                    Height::Fit => DraftOptions::DEFAULT_HEIGHT,
+                } * cell_height as f32,
+                columns: match self.height {
+                    Height::Fixed(_) => None,
+                    Height::Fit => Some(self.terminal_width * cell_width),
+                },
+                aspect_ratio: self.aspect_ratio * cell_width as f32 / cell_height as f32,
+                max_width: Some(self.terminal_width * cell_width),
+                max_size: Some(MAX_DRAFT_SIZE),
+                ..Default::default()
+            },
+        );
+
+        if !draft.missing.is_empty() {
+            self.warn_missing(&draft.missing);
+        }
+
+        // The draft is sent to the artist a few rows of cells at a time, as many as fit in the
+        // region it reads from, and with room in the region it writes to for what it completes
+        let color_mode = self.color_mode;
+        let draft_row_size = cell_height * draft.width;
+        let masterpiece_row_size =
+            draft.width.div_ceil(cell_width) * (MAX_CHAR_SIZE + color_mode.cell_size());
+        let cell_rows_per_chunk = (self.region_out.size() / draft_row_size.max(1))
+            .min(self.region_in.size() / masterpiece_row_size.max(1));
+        if cell_rows_per_chunk == 0 {
+            self.print_error(artist::ArtistError::TooLarge);
+            return;
+        }
+        let chunk_size = cell_rows_per_chunk * draft_row_size;
+
+        let subject_start = 0;
+        let subject_size = subject.len();
+        let subject_end = subject_start + subject_size;
+
+        self.region_out.write(subject_start, subject.as_bytes());
+
+        let palette = match &self.palette {
+            Palette::BuiltIn(palette) => artist::Palette::BuiltIn(*palette),
+            Palette::Custom(palette) => {
+                let palette_start = subject_end;
+                let palette_size = palette.len();
+                self.region_out.write(palette_start, palette.as_bytes());
+                artist::Palette::Custom {
+                    start: palette_start,
+                    size: palette_size,
+                }
+            }
+        };
+
+        let req = artist::Request::BeginComplete(artist::BeginCompleteRequest {
+            height: draft.height,
+            width: draft.width,
+            subject_start,
+            subject_size,
+            palette,
+            dither: self.dither,
+            render_mode: self.render_mode,
+            color_mode,
+        });
+
+        let begun = match self.artist.call(req) {
+            Ok(artist::Response::BeginComplete(resp)) => resp,
+            Ok(_) => {
+                self.print_error(artist::ArtistError::Malformed);
+                return;
+            }
+            Err(err) => {
+                self.print_error(err);
+                return;
+            }
+        };
+
+        self.newline();
+
+        // Each chunk's rows are printed as soon as they arrive
+        let mut pixel_hasher = Sha256::new();
+        for chunk in draft.pixel_data.chunks(chunk_size) {
+            let draft_start = 0;
+            let draft_size = chunk.len();
+
+            self.region_out.write(draft_start, chunk);
+
+            let req = artist::Request::CompleteChunk(artist::CompleteChunkRequest {
+                draft_start,
+                draft_size,
+            });
+
+            let resp = match self.artist.call(req) {
+                Ok(artist::Response::CompleteChunk(resp)) => resp,
+                Ok(_) => {
+                    self.print_error(artist::ArtistError::Malformed);
+                    return;
+                }
+                Err(err) => {
+                    self.print_error(err);
+                    return;
+                }
+            };
+
+            let pixel_data = self.read_region_in(resp.masterpiece_start, resp.masterpiece_size);
+
+            let colors = self.read_region_in(resp.colors_start, resp.colors_size);
+
+            pixel_hasher.update(&pixel_data);
+            self.print_rows(begun.width, &pixel_data, color_mode, &colors);
+        }
+
+        let resp = match self.artist.call(artist::Request::FinishComplete) {
+            Ok(artist::Response::FinishComplete(resp)) => resp,
+            Ok(_) => {
+                self.print_error(artist::ArtistError::Malformed);
+                return;
+            }
+            Err(err) => {
+                self.print_error(err);
+                return;
+            }
+        };
+
+        let signature = self.read_region_in(resp.signature_start, resp.signature_size);
+
+        self.newline();
+
+        writeln!(self.writer(), "Subject: {}", subject).unwrap();
+        writeln!(self.writer(), "Edition: {}", begun.edition).unwrap();
+        writeln!(
+            self.writer(),
+            "Dimensions: {}x{}",
+            begun.width,
+            begun.height
+        )
+        .unwrap();
+        writeln!(
+            self.writer(),
+            "Pixel hash: {}",
+            hex::encode(pixel_hasher.finalize())
+        )
+        .unwrap();
+        if color_mode != artist::ColorMode::Off {
+            writeln!(self.writer(), "Color: {}", color_mode.name()).unwrap();
+        }
+        writeln!(
+            self.writer(),
+            "Algorithm: {}",
+            resp.signature_algorithm.name()
+        )
+        .unwrap();
+        writeln!(self.writer(), "Signature:").unwrap();
+        for line in signature.chunks(32) {
+            writeln!(self.writer(), "{}", hex::encode(line)).unwrap();
+        }
+
+        self.newline();
+    }
+
+    // Prints whole rows of a masterpiece, `width` pixels each
+    fn print_rows(
+        &mut self,
+        width: usize,
+        pixel_data: &[u8],
+        color_mode: artist::ColorMode,
+        colors: &[u8],
+    ) {
+        let art = String::from_utf8_lossy(pixel_data);
+        let mut pixels = art.chars().peekable();
+        let cell_size = color_mode.cell_size();
+        let mut cells = colors.chunks_exact(cell_size.max(1));
+        while pixels.peek().is_some() {
+            // Escape sequences are only written where the colours change
+            let mut current = None;
+            for c in pixels.by_ref().take(width) {
+                if cell_size > 0 {
+                    let cell = cells.next();
+                    if cell != current {
+                        if let Some(cell) = cell {
+                            write_sgr(self.writer(), color_mode, cell).unwrap();
+                        }
+                        current = cell;
+                    }
+                }
+                write!(self.writer(), "{}", c).unwrap();
+            }
+            if cell_size > 0 {
+                write!(self.writer(), "{}", SGR_RESET).unwrap();
+            }
+            self.newline();
+        }
+    }
+
+    fn verify(&mut self, piece: &Piece) {
+        let metadata = &piece.metadata;
+
+        let subject_start = 0;
+        let subject_size = metadata.subject.len();
+        let subject_end = subject_start + subject_size;
+
+        let signature_start = subject_end;
+        let signature_size = piece.signature.len();
+        let signature_end = signature_start + signature_size;
+
+        if signature_end > self.region_out.size() {
+            self.print_error(artist::ArtistError::TooLarge);
+            return;
+        }
+
+        self.region_out
+            .write(subject_start, metadata.subject.as_bytes());
+
+        self.region_out.write(signature_start, &piece.signature);
+
+        let req = artist::Request::BeginVerify(artist::BeginVerifyRequest {
+            height: metadata.dimensions.1,
+            width: metadata.dimensions.0,
+            subject_start,
+            subject_size,
+            edition: metadata.edition,
+            color_mode: metadata.color_mode,
+            signature_start,
+            signature_size,
+            signature_algorithm: metadata.signature_algorithm,
+        });
+
+        if let Err(err) = self.artist.call(req) {
+            self.print_error(err);
+            return;
+        }
+
+        // Each chunk holds as much of the pixel data as fits, split at a char boundary, and then as
+        // many of the colours as fit in the rest of the region
+        let mut pixel_data = piece.pixel_data.as_str();
+        let mut colors = piece.colors.as_slice();
+        while !pixel_data.is_empty() || !colors.is_empty() {
+            let mut masterpiece_size = pixel_data.len().min(self.region_out.size());
+            while !pixel_data.is_char_boundary(masterpiece_size) {
+                masterpiece_size -= 1;
+            }
+            let (masterpiece_chunk, rest) = pixel_data.split_at(masterpiece_size);
+            pixel_data = rest;
+
+            let colors_size = colors.len().min(self.region_out.size() - masterpiece_size);
+            let (colors_chunk, rest) = colors.split_at(colors_size);
+            colors = rest;
+
+            let masterpiece_start = 0;
+            let masterpiece_end = masterpiece_start + masterpiece_size;
+
+            self.region_out
+                .write(masterpiece_start, masterpiece_chunk.as_bytes());
+
+            let colors_start = masterpiece_end;
+
+            self.region_out.write(colors_start, colors_chunk);
+
+            let req = artist::Request::VerifyChunk(artist::VerifyChunkRequest {
+                masterpiece_start,
+                masterpiece_size,
+                colors_start,
+                colors_size,
+            });
+
+            if let Err(err) = self.artist.call(req) {
+                self.print_error(err);
+                return;
+            }
+        }
+
+        let resp = match self.artist.call(artist::Request::FinishVerify) {
+            Ok(artist::Response::FinishVerify(resp)) => resp,
+            Ok(_) => {
+                self.print_error(artist::ArtistError::Malformed);
+                return;
+            }
+            Err(err) => {
+                self.print_error(err);
+                return;
+            }
+        };
+
+        self.newline();
+
+        if resp.valid {
+            writeln!(self.writer(), "Signature is valid.").unwrap();
+        } else {
+            writeln!(self.writer(), "Signature is NOT valid.").unwrap();
+        }
+
+        self.newline();
+    }
+
+    fn print_public_key(&mut self) {
+        let resp = match self.artist.call(artist::Request::GetPublicKey) {
+            Ok(artist::Response::GetPublicKey(resp)) => resp,
+            Ok(_) => {
+                self.print_error(artist::ArtistError::Malformed);
+                return;
+            }
+            Err(err) => {
+                self.print_error(err);
+                return;
+            }
+        };
+
+        let public_key = self.read_region_in(resp.public_key_start, resp.public_key_size);
+
+        let fingerprint = Sha256::digest(&public_key);
+
+        self.newline();
+
+        writeln!(
+            self.writer(),
+            "Algorithm: {}",
+            resp.signature_algorithm.name()
+        )
+        .unwrap();
+        writeln!(self.writer(), "Public key fingerprint (SHA-256):").unwrap();
+        writeln!(self.writer(), "{}", hex::encode(fingerprint)).unwrap();
+
+        self.newline();
+    }
+
+    fn print_typefaces(&mut self) {
+        self.newline();
+        for typeface in Typeface::ALL {
+            let marker = if *typeface == self.typeface { '*' } else { ' ' };
+            writeln!(self.writer(), "{} {}", marker, typeface.name()).unwrap();
+        }
+        self.newline();
+    }
+
+    fn set_typeface(&mut self, name: &str) {
+        match Typeface::from_name(name) {
+            Some(typeface) => {
+                self.typeface = typeface;
+            }
+            None => {
+                writeln!(
+                    self.writer(),
+                    "error: unknown font {:?} (enter \"font\" to list fonts)",
+                    name
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    fn warn_missing(&mut self, missing: &[char]) {
+        self.newline();
+        write!(self.writer(), "warning: no bundled font can draw").unwrap();
+        for c in missing {
+            write!(self.writer(), " {:?}", c).unwrap();
+        }
+        writeln!(
+            self.writer(),
+            "; such characters are drawn as boxes or not at all"
+        )
+        .unwrap();
+    }
+
+    fn print_palettes(&mut self) {
+        self.newline();
+        for palette in artist::BuiltInPalette::ALL {
+            let current = matches!(self.palette, Palette::BuiltIn(p) if p == *palette);
+            let marker = if current { '*' } else { ' ' };
+            writeln!(self.writer(), "{} {}", marker, palette.name()).unwrap();
+        }
+        if let Palette::Custom(palette) = &self.palette {
+            let palette = palette.clone();
+            writeln!(self.writer(), "* custom {:?}", palette).unwrap();
+        }
+        self.newline();
+    }
+
+    fn set_palette(&mut self, name: &str) {
+        match artist::BuiltInPalette::from_name(name) {
+            Some(palette) => {
+                self.palette = Palette::BuiltIn(palette);
+            }
+            None => {
+                writeln!(
+                    self.writer(),
+                    "error: unknown palette {:?} (enter \"palette\" to list palettes)",
+                    name
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    fn set_custom_palette(&mut self, palette: &str) {
+        let len = palette.chars().count();
+        if (artist::MIN_CUSTOM_PALETTE_LEN..=artist::MAX_CUSTOM_PALETTE_LEN).contains(&len) {
+            self.palette = Palette::Custom(palette.into());
+        } else {
+            writeln!(
+                self.writer(),
+                "error: a custom palette must have from {} to {} characters, from blank to inked",
+                artist::MIN_CUSTOM_PALETTE_LEN,
+                artist::MAX_CUSTOM_PALETTE_LEN,
+            )
+            .unwrap();
+        }
+    }
+
+    fn print_dithers(&mut self) {
+        self.newline();
+        for dither in artist::Dither::ALL {
+            let marker = if *dither == self.dither { '*' } else { ' ' };
+            writeln!(self.writer(), "{} {}", marker, dither.name()).unwrap();
+        }
+        self.newline();
+    }
+
+    fn set_dither(&mut self, name: &str) {
+        match artist::Dither::from_name(name) {
+            Some(dither) => {
+                self.dither = dither;
+            }
+            None => {
+                writeln!(
+                    self.writer(),
+                    "error: unknown dithering mode {:?} (enter \"dither\" to list modes)",
+                    name
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    fn print_render_modes(&mut self) {
+        self.newline();
+        for render_mode in artist::RenderMode::ALL {
+            let marker = if *render_mode == self.render_mode {
+                '*'
+            } else {
+                ' '
+            };
+            writeln!(self.writer(), "{} {}", marker, render_mode.name()).unwrap();
+        }
+        self.newline();
+    }
+
+    fn set_render_mode(&mut self, name: &str) {
+        match artist::RenderMode::from_name(name) {
+            Some(render_mode) => {
+                self.render_mode = render_mode;
+            }
+            None => {
+                writeln!(
+                    self.writer(),
+                    "error: unknown render mode {:?} (enter \"render\" to list modes)",
+                    name
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    fn print_color_modes(&mut self) {
+        self.newline();
+        for color_mode in artist::ColorMode::ALL {
+            let marker = if *color_mode == self.color_mode {
+                '*'
+            } else {
+                ' '
+            };
+            writeln!(self.writer(), "{} {}", marker, color_mode.name()).unwrap();
+        }
+        self.newline();
+    }
+
+    fn set_color_mode(&mut self, name: &str) {
+        match artist::ColorMode::from_name(name) {
+            Some(color_mode) => {
+                self.color_mode = color_mode;
+            }
+            None => {
+                writeln!(
+                    self.writer(),
+                    "error: unknown color mode {:?} (enter \"color\" to list modes)",
+                    name
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    fn print_size(&mut self) {
+        let terminal_width = self.terminal_width;
+        let height = self.height;
+        let aspect_ratio = self.aspect_ratio;
+        self.newline();
+        writeln!(self.writer(), "Terminal width: {}", terminal_width).unwrap();
+        match height {
+            Height::Fixed(height) => writeln!(self.writer(), "Height: {}", height).unwrap(),
+            Height::Fit => writeln!(self.writer(), "Height: fit").unwrap(),
+        }
+        writeln!(self.writer(), "Aspect ratio: {}", aspect_ratio).unwrap();
+        self.newline();
+    }
+
+    fn set_terminal_width(&mut self, arg: &str) {
+        match arg.parse() {
+            Ok(width) if TERMINAL_WIDTH_RANGE.contains(&width) => {
+                self.terminal_width = width;
+            }
+            _ => {
+                writeln!(
+                    self.writer(),
+                    "error: width must be a number of columns from {} to {}",
+                    TERMINAL_WIDTH_RANGE.start(),
+                    TERMINAL_WIDTH_RANGE.end(),
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    fn set_height(&mut self, arg: &str) {
+        match arg {
+            "fit" => {
+                self.height = Height::Fit;
+            }
+            _ => match arg.parse() {
+                Ok(height) if HEIGHT_RANGE.contains(&height) => {
+                    self.height = Height::Fixed(height);
+                }
+                _ => {
+                    writeln!(
+                        self.writer(),
+                        "error: height must be \"fit\" or a number of rows from {} to {}",
+                        HEIGHT_RANGE.start(),
+                        HEIGHT_RANGE.end(),
+                    )
+                    .unwrap();
+                }
+            },
+        }
+    }
+
+    fn set_aspect_ratio(&mut self, arg: &str) {
+        match arg.parse() {
+            Ok(aspect_ratio) if ASPECT_RATIO_RANGE.contains(&aspect_ratio) => {
+                self.aspect_ratio = aspect_ratio;
+            }
+            _ => {
+                writeln!(
+                    self.writer(),
+                    "error: aspect ratio must be a number from {} to {}",
+                    ASPECT_RATIO_RANGE.start(),
+                    ASPECT_RATIO_RANGE.end(),
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    fn print_status(&mut self) {
+        let resp = match self.artist.call(artist::Request::GetStatus) {
+            Ok(artist::Response::GetStatus(resp)) => resp,
+            Ok(_) => {
+                self.print_error(artist::ArtistError::Malformed);
+                return;
+            }
+            Err(err) => {
+                self.print_error(err);
+                return;
+            }
+        };
+
+        self.newline();
+
+        writeln!(
+            self.writer(),
+            "Signatures remaining this window: {}/{} (window: {}s, resets in {}s)",
+            resp.window_remaining,
+            resp.window_limit,
+            resp.window_ms / 1000,
+            resp.window_resets_in_ms.div_ceil(1000),
+        )
+        .unwrap();
+        writeln!(
+            self.writer(),
+            "Signatures remaining in lifetime quota: {}/{}",
+            resp.lifetime_remaining,
+            resp.lifetime_quota,
+        )
+        .unwrap();
+
+        self.newline();
+    }
+
+    fn print_error(&mut self, err: artist::ArtistError) {
+        self.newline();
+        writeln!(self.writer(), "error: {}", err).unwrap();
+        self.newline();
+    }
+
+    fn read_region_in(&self, start: usize, size: usize) -> Vec<u8> {
+        let mut buf = vec![0; size];
+        self.region_in.read(start, &mut buf);
+        buf
+    }
+
+    fn prompt(&mut self) {
+        write!(self.writer(), "banscii> ").unwrap();
+    }
+
+    fn newline(&mut self) {
+        writeln!(self.writer()).unwrap();
+    }
+
+    fn writer(&mut self) -> &mut dyn serial::Write<Error = T::Error> {
+        &mut self.serial as &mut dyn serial::Write<Error = T::Error>
+    }
+}
+
+// Selects the colours of a cell, encoded as described by `artist::ColorMode`
+fn write_sgr<W: Write + ?Sized>(
+    w: &mut W,
+    color_mode: artist::ColorMode,
+    cell: &[u8],
+) -> fmt::Result {
+    let (fg, bg) = cell.split_at(cell.len() / 2);
+    match color_mode {
+        artist::ColorMode::Off => Ok(()),
+        artist::ColorMode::Ansi16 => {
+            // The bright colours have codes of their own
+            let code = |base: u32, index: u8| match index {
+                0..=7 => base + u32::from(index),
+                _ => base + 60 + u32::from(index) - 8,
+            };
+            write!(w, "\x1b[{};{}m", code(30, fg[0]), code(40, bg[0]))
+        }
+        artist::ColorMode::Ansi256 => write!(w, "\x1b[38;5;{};48;5;{}m", fg[0], bg[0]),
+        artist::ColorMode::TrueColor => write!(
+            w,
+            "\x1b[38;2;{};{};{};48;2;{};{};{}m",
+            fg[0], fg[1], fg[2], bg[0], bg[1], bg[2]
+        ),
+    }
+}
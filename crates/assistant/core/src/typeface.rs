@@ -0,0 +1,71 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+// Each typeface is bundled only if its `typeface-*` feature is enabled
+
+#[cfg(not(any(
+    feature = "typeface-rock-salt",
+    feature = "typeface-dejavu-serif",
+    feature = "typeface-dejavu-sans-mono"
+)))]
+compile_error!("at least one of the typeface-* features must be enabled");
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Typeface {
+    #[cfg(feature = "typeface-rock-salt")]
+    RockSalt,
+    #[cfg(feature = "typeface-dejavu-serif")]
+    DejaVuSerif,
+    #[cfg(feature = "typeface-dejavu-sans-mono")]
+    DejaVuSansMono,
+}
+
+impl Typeface {
+    pub const ALL: &'static [Self] = &[
+        #[cfg(feature = "typeface-rock-salt")]
+        Self::RockSalt,
+        #[cfg(feature = "typeface-dejavu-serif")]
+        Self::DejaVuSerif,
+        #[cfg(feature = "typeface-dejavu-sans-mono")]
+        Self::DejaVuSansMono,
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "typeface-rock-salt")]
+            Self::RockSalt => "rock-salt",
+            #[cfg(feature = "typeface-dejavu-serif")]
+            Self::DejaVuSerif => "dejavu-serif",
+            #[cfg(feature = "typeface-dejavu-sans-mono")]
+            Self::DejaVuSansMono => "dejavu-sans-mono",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|typeface| typeface.name() == name)
+    }
+
+    pub(crate) const fn font_data(self) -> &'static [u8] {
+        match self {
+            #[cfg(feature = "typeface-rock-salt")]
+            Self::RockSalt => include_bytes!("../assets/fonts/rock-salt/RockSalt-Regular.ttf"),
+            #[cfg(feature = "typeface-dejavu-serif")]
+            Self::DejaVuSerif => include_bytes!("../assets/fonts/dejavu/DejaVuSerif.ttf"),
+            #[cfg(feature = "typeface-dejavu-sans-mono")]
+            Self::DejaVuSansMono => include_bytes!("../assets/fonts/dejavu/DejaVuSansMono.ttf"),
+        }
+    }
+}
+
+// The first of those enabled, in the order above
+impl Default for Typeface {
+    fn default() -> Self {
+        Self::ALL[0]
+    }
+}
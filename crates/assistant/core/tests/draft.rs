@@ -0,0 +1,394 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+// Compares drafts against the snapshots in tests/snapshots/. To accept changes to the output,
+// run with BANSCII_UPDATE_SNAPSHOTS=1 and review the resulting diff.
+
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+
+use sha2::{Digest, Sha256};
+
+use banscii_assistant_core::{Draft, DraftOptions};
+
+const UPDATE_ENV: &str = "BANSCII_UPDATE_SNAPSHOTS";
+
+// As used by the assistant
+const MAX_WIDTH: usize = 80;
+
+// Each case is named after its snapshot
+fn cases() -> Vec<(&'static str, &'static str, DraftOptions)> {
+    vec![
+        ("empty", "", DraftOptions::default()),
+        ("single_space", " ", DraftOptions::default()),
+        ("spaces", "        ", DraftOptions::default()),
+        ("hello_world", "Hello, World!", DraftOptions::default()),
+        ("punctuation", "!?.,;:'\"()-_/@#", DraftOptions::default()),
+        ("digits", "0123456789", DraftOptions::default()),
+        ("mixed_case", "aBcDeFgHiJkLmNoP", DraftOptions::default()),
+        // RockSalt has no glyphs for these
+        ("cyrillic", "Привет", DraftOptions::default()),
+        ("cjk", "日本語", DraftOptions::default()),
+        ("emoji", "🎨🖌", DraftOptions::default()),
+        // The assistant's limit on subject length
+        ("max_length", "WWWWWWWWWWWWWWWW", DraftOptions::default()),
+        (
+            "wrapped_fits",
+            "Hello, World!",
+            DraftOptions {
+                max_width: Some(MAX_WIDTH),
+                ..Default::default()
+            },
+        ),
+        (
+            "wrapped_phrase",
+            "The quick brown fox jumps over the lazy dog",
+            DraftOptions {
+                max_width: Some(MAX_WIDTH),
+                ..Default::default()
+            },
+        ),
+        (
+            "wrapped_long_word",
+            "Supercalifragilisticexpialidocious",
+            DraftOptions {
+                max_width: Some(MAX_WIDTH),
+                ..Default::default()
+            },
+        ),
+        (
+            "wrapped_extra_spaces",
+            "  lots   of    space   between   words  ",
+            DraftOptions {
+                max_width: Some(MAX_WIDTH),
+                ..Default::default()
+            },
+        ),
+        (
+            "wrapped_newlines",
+            "one\ntwo\n\nfour",
+            DraftOptions {
+                max_width: Some(MAX_WIDTH),
+                ..Default::default()
+            },
+        ),
+        // The assistant's limit on subject length
+        (
+            "wrapped_max_length",
+            "WWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWWW",
+            DraftOptions {
+                max_width: Some(MAX_WIDTH),
+                ..Default::default()
+            },
+        ),
+        (
+            "sized_small",
+            "Hello, World!",
+            DraftOptions {
+                height: 8.0,
+                ..Default::default()
+            },
+        ),
+        (
+            "sized_square_cells",
+            "Hello, World!",
+            DraftOptions {
+                aspect_ratio: 1.0,
+                ..Default::default()
+            },
+        ),
+        (
+            "sized_padded",
+            "Hello, World!",
+            DraftOptions {
+                padding: 2,
+                ..Default::default()
+            },
+        ),
+        (
+            "sized_padded_wrapped",
+            "The quick brown fox jumps over the lazy dog",
+            DraftOptions {
+                padding: 2,
+                max_width: Some(MAX_WIDTH),
+                ..Default::default()
+            },
+        ),
+        (
+            "sized_fit_132",
+            "Hello, World!",
+            DraftOptions {
+                columns: Some(132),
+                ..Default::default()
+            },
+        ),
+        (
+            "sized_fit_narrow",
+            "Hello, World!",
+            DraftOptions {
+                columns: Some(24),
+                ..Default::default()
+            },
+        ),
+        (
+            "sized_fit_padded",
+            "Hello, World!",
+            DraftOptions {
+                columns: Some(40),
+                padding: 1,
+                ..Default::default()
+            },
+        ),
+        (
+            "sized_fit_lines",
+            "one\ntwo\nthree",
+            DraftOptions {
+                columns: Some(40),
+                ..Default::default()
+            },
+        ),
+        (
+            "sized_fit_empty",
+            "",
+            DraftOptions {
+                columns: Some(40),
+                ..Default::default()
+            },
+        ),
+        #[cfg(feature = "typeface-dejavu-serif")]
+        (
+            "dejavu_serif",
+            "Hello, World!",
+            DraftOptions {
+                typeface: banscii_assistant_core::Typeface::DejaVuSerif,
+                max_width: Some(MAX_WIDTH),
+                ..Default::default()
+            },
+        ),
+        #[cfg(feature = "typeface-dejavu-serif")]
+        (
+            "dejavu_serif_cyrillic",
+            "Привет",
+            DraftOptions {
+                typeface: banscii_assistant_core::Typeface::DejaVuSerif,
+                max_width: Some(MAX_WIDTH),
+                ..Default::default()
+            },
+        ),
+        #[cfg(feature = "typeface-dejavu-sans-mono")]
+        (
+            "dejavu_sans_mono",
+            "Hello, World!",
+            DraftOptions {
+                typeface: banscii_assistant_core::Typeface::DejaVuSansMono,
+                max_width: Some(MAX_WIDTH),
+                ..Default::default()
+            },
+        ),
+        #[cfg(feature = "typeface-dejavu-serif")]
+        (
+            "fallback_cyrillic",
+            "Hi, мир!",
+            DraftOptions {
+                fallbacks: vec![banscii_assistant_core::Typeface::DejaVuSerif],
+                max_width: Some(MAX_WIDTH),
+                ..Default::default()
+            },
+        ),
+        #[cfg(feature = "typeface-dejavu-serif")]
+        (
+            "fallback_emoji",
+            "Hi 🎨",
+            DraftOptions {
+                fallbacks: vec![banscii_assistant_core::Typeface::DejaVuSerif],
+                max_width: Some(MAX_WIDTH),
+                ..Default::default()
+            },
+        ),
+        #[cfg(all(
+            feature = "typeface-dejavu-serif",
+            feature = "typeface-dejavu-sans-mono"
+        ))]
+        (
+            "fallback_chain",
+            "Hi, мир!",
+            DraftOptions {
+                fallbacks: vec![
+                    banscii_assistant_core::Typeface::DejaVuSansMono,
+                    banscii_assistant_core::Typeface::DejaVuSerif,
+                ],
+                max_width: Some(MAX_WIDTH),
+                ..Default::default()
+            },
+        ),
+    ]
+}
+
+#[test]
+fn snapshots() {
+    let mismatches = cases()
+        .iter()
+        .filter_map(|(name, subject, options)| check_snapshot(name, subject, options).err())
+        .collect::<Vec<_>>();
+    assert!(mismatches.is_empty(), "{}", mismatches.join("\n"));
+}
+
+#[test]
+fn fit_fills_columns() {
+    for subject in ["Hello, World!", "WWWWWWWWWWWWWWWW", "i", "one\ntwo\nthree"] {
+        for columns in [8, 24, 40, 80, 132] {
+            let draft = Draft::with_options(
+                subject,
+                &DraftOptions {
+                    columns: Some(columns),
+                    ..Default::default()
+                },
+            );
+            assert!(draft.width <= columns, "{subject:?} in {columns} columns");
+            assert!(
+                draft.width + 2 >= columns,
+                "{subject:?} in {columns} columns"
+            );
+        }
+    }
+}
+
+#[test]
+fn missing_none() {
+    assert_eq!(Draft::new("Hello, World!").missing, []);
+}
+
+#[test]
+fn missing_in_order_of_appearance() {
+    // RockSalt has no glyphs for these
+    assert_eq!(Draft::new("Hi, мир, Мир!").missing, ['м', 'и', 'р', 'М']);
+}
+
+#[test]
+fn missing_across_wrapped_lines() {
+    let draft = Draft::with_options(
+        "The quick brown fox jumps over the lazy 🐕",
+        &DraftOptions {
+            max_width: Some(MAX_WIDTH),
+            ..Default::default()
+        },
+    );
+    assert!(draft.height > Draft::new("").height);
+    assert_eq!(draft.missing, ['🐕']);
+}
+
+#[test]
+fn max_size() {
+    let options = DraftOptions {
+        height: 200.0,
+        max_width: Some(MAX_WIDTH),
+        ..Default::default()
+    };
+    let max_size = 0x1000;
+    let unbounded = Draft::with_options("Hello, World!", &options);
+    let draft = Draft::with_options(
+        "Hello, World!",
+        &DraftOptions {
+            max_size: Some(max_size),
+            ..options.clone()
+        },
+    );
+    assert!(unbounded.pixel_data.len() > max_size);
+    assert!(draft.pixel_data.len() <= max_size);
+    assert!(draft.pixel_data.iter().any(|grey| *grey != 0));
+
+    // Padding is never shrunk
+    let draft = Draft::with_options(
+        "Hello, World!",
+        &DraftOptions {
+            padding: 40,
+            max_size: Some(max_size),
+            ..options
+        },
+    );
+    assert!(draft.pixel_data.len() > max_size);
+}
+
+#[cfg(feature = "typeface-dejavu-serif")]
+#[test]
+fn missing_with_fallback() {
+    let draft = Draft::with_options(
+        "Hi, мир 🐕",
+        &DraftOptions {
+            fallbacks: vec![banscii_assistant_core::Typeface::DejaVuSerif],
+            ..Default::default()
+        },
+    );
+    assert_eq!(draft.missing, ['🐕']);
+}
+
+fn check_snapshot(name: &str, subject: &str, options: &DraftOptions) -> Result<(), String> {
+    let actual = render(subject, options);
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{name}.txt"));
+    if env::var_os(UPDATE_ENV).is_some() {
+        fs::write(&path, &actual).unwrap();
+        return Ok(());
+    }
+    let expected = fs::read_to_string(&path).map_err(|err| {
+        format!(
+            "failed to read {} ({err}), run with {UPDATE_ENV}=1 to create it",
+            path.display()
+        )
+    })?;
+    if actual != expected {
+        return Err(format!(
+            "draft of {subject:?} does not match {}, run with {UPDATE_ENV}=1 to update it\n\
+            expected:\n{expected}\nactual:\n{actual}",
+            path.display(),
+        ));
+    }
+    Ok(())
+}
+
+fn render(subject: &str, options: &DraftOptions) -> String {
+    let draft = Draft::with_options(subject, options);
+    assert_eq!(draft.pixel_data.len(), draft.width * draft.height);
+    if let Some(max_width) = options.max_width {
+        assert!(draft.width <= max_width);
+    }
+    if let Some(columns) = options.columns {
+        assert!(draft.width <= columns);
+    }
+
+    let mut s = String::new();
+    writeln!(s, "Subject: {subject:?}").unwrap();
+    writeln!(s, "Dimensions: {}x{}", draft.width, draft.height).unwrap();
+    if !draft.missing.is_empty() {
+        writeln!(s, "Missing: {:?}", draft.missing).unwrap();
+    }
+    writeln!(
+        s,
+        "Pixel hash: {}",
+        hex::encode(Sha256::digest(&draft.pixel_data))
+    )
+    .unwrap();
+    writeln!(s).unwrap();
+    for row in draft.pixel_data.chunks(draft.width.max(1)) {
+        for grey in row {
+            s.push(shade(*grey));
+        }
+        // Mark the right edge, so that trailing blank columns are visible
+        s.push_str("|\n");
+    }
+    s
+}
+
+// Blank pixels are left blank, and others are shown by the high digit of their grey level in hex
+fn shade(grey: u8) -> char {
+    match grey {
+        0 => ' ',
+        _ => char::from_digit((grey >> 4).into(), 16).unwrap(),
+    }
+}
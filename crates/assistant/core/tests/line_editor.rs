@@ -0,0 +1,261 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+use banscii_assistant_core::LineEditor;
+
+const LEFT: &str = "\x1b[D";
+const RIGHT: &str = "\x1b[C";
+const UP: &str = "\x1b[A";
+const DOWN: &str = "\x1b[B";
+const HOME: &str = "\x1b[H";
+const END: &str = "\x1b[F";
+const DELETE: &str = "\x1b[3~";
+const BACKSPACE: &str = "\x7f";
+
+// Feeds `input` to `editor`, and returns what was echoed
+fn feed(editor: &mut LineEditor, input: &str) -> String {
+    feed_with_limit(editor, input, None)
+}
+
+fn feed_with_limit(editor: &mut LineEditor, input: &str, max_len: Option<usize>) -> String {
+    let mut out = String::new();
+    for c in input.chars() {
+        editor.handle_char(c, max_len, &mut out).unwrap();
+    }
+    out
+}
+
+fn submit(editor: &mut LineEditor, input: &str) {
+    feed(editor, input);
+    let line = editor.take_line();
+    editor.add_to_history(&line);
+}
+
+#[test]
+fn typing_is_echoed() {
+    let mut editor = LineEditor::new();
+    assert_eq!(feed(&mut editor, "Zoë"), "Zoë");
+    assert_eq!(editor.line(), "Zoë");
+    assert_eq!(editor.cursor(), 3);
+}
+
+#[test]
+fn other_control_chars_are_ignored() {
+    let mut editor = LineEditor::new();
+    assert_eq!(feed(&mut editor, "a\tb\x00c"), "abc");
+    assert_eq!(editor.line(), "abc");
+}
+
+#[test]
+fn backspace() {
+    let mut editor = LineEditor::new();
+    feed(&mut editor, "helloo");
+    assert_eq!(feed(&mut editor, BACKSPACE), "\x1b[1D\x1b[K");
+    assert_eq!(editor.line(), "hello");
+    feed(&mut editor, "\x08");
+    assert_eq!(editor.line(), "hell");
+}
+
+#[test]
+fn backspace_at_start() {
+    let mut editor = LineEditor::new();
+    assert_eq!(feed(&mut editor, BACKSPACE), "");
+    feed(&mut editor, &format!("ab{HOME}"));
+    assert_eq!(feed(&mut editor, BACKSPACE), "");
+    assert_eq!(editor.line(), "ab");
+}
+
+#[test]
+fn insert_in_middle() {
+    let mut editor = LineEditor::new();
+    feed(&mut editor, &format!("helo{LEFT}"));
+    assert_eq!(editor.cursor(), 3);
+    // The rest of the line is redrawn, and the cursor put back
+    assert_eq!(feed(&mut editor, "l"), "lo\x1b[K\x1b[1D");
+    assert_eq!(editor.line(), "hello");
+    assert_eq!(editor.cursor(), 4);
+}
+
+#[test]
+fn backspace_in_middle() {
+    let mut editor = LineEditor::new();
+    feed(&mut editor, &format!("helxlo{LEFT}{LEFT}"));
+    assert_eq!(feed(&mut editor, BACKSPACE), "\x1b[1Dlo\x1b[K\x1b[2D");
+    assert_eq!(editor.line(), "hello");
+    assert_eq!(editor.cursor(), 3);
+}
+
+#[test]
+fn delete() {
+    let mut editor = LineEditor::new();
+    feed(&mut editor, &format!("helxlo{LEFT}{LEFT}{LEFT}"));
+    assert_eq!(feed(&mut editor, DELETE), "lo\x1b[K\x1b[2D");
+    assert_eq!(editor.line(), "hello");
+    assert_eq!(feed(&mut editor, &format!("{END}{DELETE}")), "\x1b[2C");
+    assert_eq!(editor.line(), "hello");
+}
+
+#[test]
+fn cursor_stays_within_line() {
+    let mut editor = LineEditor::new();
+    feed(&mut editor, "ab");
+    assert_eq!(feed(&mut editor, RIGHT), "");
+    assert_eq!(
+        feed(&mut editor, &format!("{LEFT}{LEFT}")),
+        "\x1b[1D\x1b[1D"
+    );
+    assert_eq!(feed(&mut editor, LEFT), "");
+    assert_eq!(editor.cursor(), 0);
+    assert_eq!(feed(&mut editor, RIGHT), "\x1b[1C");
+    assert_eq!(editor.cursor(), 1);
+}
+
+#[test]
+fn home_and_end() {
+    // Terminals disagree on how to encode these
+    for (home, end) in [
+        (HOME, END),
+        ("\x1bOH", "\x1bOF"),
+        ("\x1b[1~", "\x1b[4~"),
+        ("\x1b[7~", "\x1b[8~"),
+        ("\x01", "\x05"),
+    ] {
+        let mut editor = LineEditor::new();
+        feed(&mut editor, "hello");
+        assert_eq!(feed(&mut editor, home), "\x1b[5D");
+        assert_eq!(editor.cursor(), 0);
+        assert_eq!(feed(&mut editor, end), "\x1b[5C");
+        assert_eq!(editor.cursor(), 5);
+    }
+}
+
+#[test]
+fn ss3_cursor_keys() {
+    let mut editor = LineEditor::new();
+    feed(&mut editor, "ac\x1bOD");
+    feed(&mut editor, "b\x1bOC");
+    assert_eq!(editor.line(), "abc");
+    assert_eq!(editor.cursor(), 3);
+}
+
+#[test]
+fn unknown_escape_sequences_are_ignored() {
+    let mut editor = LineEditor::new();
+    // Page up, F5, Alt-x, and Ctrl-Right, the last of which is taken as Right
+    assert_eq!(feed(&mut editor, "a\x1b[5~\x1b[15~\x1bxb"), "ab");
+    feed(&mut editor, &format!("{HOME}\x1b[1;5C"));
+    assert_eq!(editor.line(), "ab");
+    assert_eq!(editor.cursor(), 1);
+}
+
+#[test]
+fn ctrl_u() {
+    let mut editor = LineEditor::new();
+    feed(&mut editor, &format!("hello world{LEFT}{LEFT}"));
+    assert_eq!(feed(&mut editor, "\x15"), "\x1b[9Dld\x1b[K\x1b[2D");
+    assert_eq!(editor.line(), "ld");
+    assert_eq!(editor.cursor(), 0);
+}
+
+#[test]
+fn ctrl_w() {
+    let mut editor = LineEditor::new();
+    feed(&mut editor, "make some  art  ");
+    feed(&mut editor, "\x17");
+    assert_eq!(editor.line(), "make some  ");
+    feed(&mut editor, "\x17");
+    assert_eq!(editor.line(), "make ");
+    feed(&mut editor, "\x17\x17");
+    assert_eq!(editor.line(), "");
+}
+
+#[test]
+fn ctrl_w_in_middle() {
+    let mut editor = LineEditor::new();
+    feed(&mut editor, &format!("make some art{LEFT}{LEFT}{LEFT}"));
+    feed(&mut editor, "\x17");
+    assert_eq!(editor.line(), "make art");
+    assert_eq!(editor.cursor(), 5);
+}
+
+#[test]
+fn limit() {
+    let mut editor = LineEditor::new();
+    assert_eq!(feed_with_limit(&mut editor, "abcd", Some(3)), "abc\x07");
+    assert_eq!(editor.line(), "abc");
+    feed_with_limit(&mut editor, &format!("{LEFT}{BACKSPACE}x"), Some(3));
+    assert_eq!(editor.line(), "axc");
+}
+
+#[test]
+fn take_line_resets() {
+    let mut editor = LineEditor::new();
+    feed(&mut editor, &format!("hello{LEFT}\x1b["));
+    assert_eq!(editor.take_line(), "hello");
+    assert_eq!(editor.line(), "");
+    assert_eq!(editor.cursor(), 0);
+    // The unfinished escape sequence is dropped too
+    feed(&mut editor, "A");
+    assert_eq!(editor.line(), "A");
+}
+
+#[test]
+fn history() {
+    let mut editor = LineEditor::new();
+    submit(&mut editor, "first");
+    submit(&mut editor, "second");
+    feed(&mut editor, "thi");
+    assert_eq!(feed(&mut editor, UP), "\x1b[3Dsecond\x1b[K");
+    feed(&mut editor, UP);
+    assert_eq!(editor.line(), "first");
+    // There is nothing older
+    assert_eq!(feed(&mut editor, UP), "");
+    assert_eq!(editor.line(), "first");
+    feed(&mut editor, DOWN);
+    assert_eq!(editor.line(), "second");
+    // Back to what was being typed
+    feed(&mut editor, DOWN);
+    assert_eq!(editor.line(), "thi");
+    assert_eq!(editor.cursor(), 3);
+    assert_eq!(feed(&mut editor, DOWN), "");
+}
+
+#[test]
+fn history_entries_can_be_edited() {
+    let mut editor = LineEditor::new();
+    submit(&mut editor, "font rock-salt");
+    feed(&mut editor, UP);
+    feed(&mut editor, "\x17dejavu-serif");
+    assert_eq!(editor.take_line(), "font dejavu-serif");
+    feed(&mut editor, UP);
+    assert_eq!(editor.line(), "font rock-salt");
+}
+
+#[test]
+fn history_skips_empty_lines_and_repeats() {
+    let mut editor = LineEditor::new();
+    submit(&mut editor, "status");
+    submit(&mut editor, "");
+    submit(&mut editor, "status");
+    submit(&mut editor, "pubkey");
+    submit(&mut editor, "status");
+    feed(&mut editor, &format!("{UP}{UP}{UP}"));
+    assert_eq!(editor.line(), "status");
+    feed(&mut editor, UP);
+    assert_eq!(editor.line(), "status");
+    feed(&mut editor, DOWN);
+    assert_eq!(editor.line(), "pubkey");
+}
+
+#[test]
+fn history_is_bounded() {
+    let mut editor = LineEditor::new();
+    for i in 0..100 {
+        submit(&mut editor, &i.to_string());
+    }
+    feed(&mut editor, &UP.repeat(100));
+    assert_eq!(editor.line(), "68");
+}
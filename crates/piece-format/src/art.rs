@@ -0,0 +1,204 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use banscii_artist_interface_types::ColorMode;
+
+// A masterpiece's art rows, with their pixels separated from the SGR escape sequences that colour
+// them. Masterpieces are UTF-8, with a char per pixel. Colours are encoded as they are read, so that
+// the art takes little more memory than the masterpiece itself.
+#[derive(Default)]
+pub struct Art {
+    // Each row's pixels, followed by '\n'
+    rows: String,
+    // As for `ColorMode::Ansi256` if `color_kind` is `ColorKind::Indexed`, and for
+    // `ColorMode::TrueColor` if it is `ColorKind::Rgb`
+    colors: Vec<u8>,
+    color_kind: Option<ColorKind>,
+    // Whether any pixel so far has no colours
+    uncolored: bool,
+}
+
+#[derive(Copy, Clone)]
+enum Color {
+    Indexed(u8),
+    Rgb([u8; 3]),
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum ColorKind {
+    Indexed,
+    Rgb,
+}
+
+// A pixel's foreground and background, where set
+type CellColors = (Option<Color>, Option<Color>);
+
+const UNSUPPORTED_ESCAPE: &str = "unsupported escape sequence in masterpiece";
+
+const COLOR_MISMATCH: &str = "masterpiece colours do not match its color mode";
+
+// Terminals strip trailing spaces from text copied out of them, so rows may come back short, and
+// blank rows empty. Only spaces are stripped, so only spaces need restoring.
+const PADDING: char = ' ';
+
+impl Art {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    // The size of the pixel data and colours so far, counting a byte for each row's line ending
+    pub fn size(&self) -> usize {
+        self.rows.len() + self.colors.len()
+    }
+
+    pub fn push_row(&mut self, row: &str) -> Result<(), &'static str> {
+        // Every row is printed from the terminal's default colours
+        let mut colors = (None, None);
+        let mut rest = row;
+        while let Some((pixels, escape)) = rest.split_once('\x1b') {
+            self.push_pixels(pixels, colors)?;
+            let (params, after) = escape
+                .strip_prefix('[')
+                .and_then(|escape| escape.split_once('m'))
+                .ok_or(UNSUPPORTED_ESCAPE)?;
+            apply_sgr(params, &mut colors)?;
+            rest = after;
+        }
+        self.push_pixels(rest, colors)?;
+        self.rows.push('\n');
+        Ok(())
+    }
+
+    // Pixels either all have both colours, of the same kind, or all have none, whatever the
+    // masterpiece's color mode
+    fn push_pixels(&mut self, pixels: &str, colors: CellColors) -> Result<(), &'static str> {
+        if pixels.is_empty() {
+            return Ok(());
+        }
+        let mut encoded = [0; 6];
+        let (kind, encoded) = match colors {
+            (None, None) => {
+                if self.color_kind.is_some() {
+                    return Err(COLOR_MISMATCH);
+                }
+                self.uncolored = true;
+                self.rows.push_str(pixels);
+                return Ok(());
+            }
+            (Some(Color::Indexed(fg)), Some(Color::Indexed(bg))) => {
+                encoded[..2].copy_from_slice(&[fg, bg]);
+                (ColorKind::Indexed, &encoded[..2])
+            }
+            (Some(Color::Rgb(fg)), Some(Color::Rgb(bg))) => {
+                encoded[..3].copy_from_slice(&fg);
+                encoded[3..].copy_from_slice(&bg);
+                (ColorKind::Rgb, &encoded[..])
+            }
+            _ => return Err(COLOR_MISMATCH),
+        };
+        if self.uncolored || *self.color_kind.get_or_insert(kind) != kind {
+            return Err(COLOR_MISMATCH);
+        }
+        self.rows.push_str(pixels);
+        for _ in pixels.chars() {
+            self.colors.extend_from_slice(encoded);
+        }
+        Ok(())
+    }
+
+    // Takes the last rows as a masterpiece of `dimensions`, padding them as needed, and returns its
+    // pixel data, and its colours encoded as described by `color_mode`. Any rows before those must
+    // be empty.
+    pub fn finish(
+        self,
+        dimensions: (usize, usize),
+        color_mode: ColorMode,
+    ) -> Result<(String, Vec<u8>), &'static str> {
+        let (width, height) = dimensions;
+        if width == 0 || height == 0 {
+            return Err("masterpiece is empty");
+        }
+        let start = match self.rows.rmatch_indices('\n').nth(height) {
+            Some((end, _)) => end + 1,
+            None => 0,
+        };
+        let rows = &self.rows[start..];
+        if self.rows[..start].bytes().any(|b| b != b'\n') || rows.matches('\n').count() != height {
+            return Err("dimensions do not match masterpiece");
+        }
+
+        let mut padding = 0;
+        for row in rows.split_terminator('\n') {
+            padding += width
+                .checked_sub(row.chars().count())
+                .ok_or("dimensions do not match masterpiece")?;
+        }
+
+        // Padding has no colours, so only a masterpiece without any can be padded
+        let colors_match = match (color_mode, self.color_kind) {
+            (ColorMode::Off, None) => true,
+            (ColorMode::Ansi16, Some(ColorKind::Indexed)) => {
+                padding == 0 && self.colors.iter().all(|color| *color < 16)
+            }
+            (ColorMode::Ansi256, Some(ColorKind::Indexed))
+            | (ColorMode::TrueColor, Some(ColorKind::Rgb)) => padding == 0,
+            _ => false,
+        };
+        if !colors_match {
+            return Err(COLOR_MISMATCH);
+        }
+
+        let mut pixel_data = String::with_capacity(rows.len() - height + padding);
+        for row in rows.split_terminator('\n') {
+            pixel_data.push_str(row);
+            for _ in row.chars().count()..width {
+                pixel_data.push(PADDING);
+            }
+        }
+        Ok((pixel_data, self.colors))
+    }
+}
+
+// Applies the parameters of an SGR escape sequence, as printed by `Assistant::create`
+fn apply_sgr(params: &str, colors: &mut CellColors) -> Result<(), &'static str> {
+    let mut params = params.split(';').map(|param| match param {
+        "" => Ok(0),
+        _ => param.parse::<u8>().map_err(|_| UNSUPPORTED_ESCAPE),
+    });
+    while let Some(param) = params.next() {
+        let mut next = || params.next().unwrap_or(Err(UNSUPPORTED_ESCAPE));
+        match param? {
+            0 => *colors = (None, None),
+            n @ 30..=37 => colors.0 = Some(Color::Indexed(n - 30)),
+            n @ 90..=97 => colors.0 = Some(Color::Indexed(n - 90 + 8)),
+            39 => colors.0 = None,
+            n @ 40..=47 => colors.1 = Some(Color::Indexed(n - 40)),
+            n @ 100..=107 => colors.1 = Some(Color::Indexed(n - 100 + 8)),
+            49 => colors.1 = None,
+            n @ (38 | 48) => {
+                let color = match next()? {
+                    5 => Color::Indexed(next()?),
+                    2 => Color::Rgb([next()?, next()?, next()?]),
+                    _ => return Err(UNSUPPORTED_ESCAPE),
+                };
+                if n == 38 {
+                    colors.0 = Some(color);
+                } else {
+                    colors.1 = Some(color);
+                }
+            }
+            _ => return Err(UNSUPPORTED_ESCAPE),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,43 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+// Masterpieces as printed by the assistant, in `Assistant::create`:
+//
+// ```
+// <art rows>
+//
+// <metadata lines>
+// Signature:
+// <hex lines>
+//
+// ```
+//
+// The art rows of a piece in colour carry SGR escape sequences, from which its colours are taken.
+// Both the assistant, which reads pieces pasted back for verification, and the host's verifier,
+// which reads them from transcripts, parse pieces here, so that they cannot disagree on the format.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+mod art;
+mod metadata;
+mod piece_reader;
+
+pub use art::Art;
+pub use metadata::{Metadata, MetadataReader, MAX_METADATA_LINES};
+pub use piece_reader::{Piece, PieceReader};
+
+pub const SIGNATURE_HEADER: &str = "Signature:";
+
+// Appends the bytes of one of the hex lines that follow `SIGNATURE_HEADER`
+pub fn decode_signature_line(line: &str, signature: &mut Vec<u8>) -> Result<(), &'static str> {
+    let mut buf = hex::decode(line).map_err(|_| "signature is not valid hex")?;
+    signature.append(&mut buf);
+    Ok(())
+}
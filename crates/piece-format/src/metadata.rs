@@ -0,0 +1,88 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+
+use banscii_artist_interface_types::{ColorMode, SignatureAlgorithm};
+
+pub struct Metadata {
+    pub subject: String,
+    pub edition: u64,
+    // Width by height, as printed, for comparison against the masterpiece itself
+    pub dimensions: (usize, usize),
+    pub pixel_hash: [u8; 32],
+    // `ColorMode::Off` if the piece has no "Color" line
+    pub color_mode: ColorMode,
+    pub signature_algorithm: SignatureAlgorithm,
+}
+
+// A line for each key, one of which is optional
+pub const MAX_METADATA_LINES: usize = 6;
+
+// Accumulates a piece's metadata, one "<key>: <value>" line at a time
+#[derive(Default)]
+pub struct MetadataReader {
+    subject: Option<String>,
+    edition: Option<u64>,
+    dimensions: Option<(usize, usize)>,
+    pixel_hash: Option<[u8; 32]>,
+    color_mode: Option<ColorMode>,
+    signature_algorithm: Option<SignatureAlgorithm>,
+}
+
+impl MetadataReader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push_line(&mut self, line: &str) -> Result<(), &'static str> {
+        let (key, value) = line.split_once(": ").ok_or("malformed metadata")?;
+        match key {
+            "Subject" => {
+                self.subject = Some(value.to_owned());
+            }
+            "Edition" => {
+                self.edition = Some(value.parse().map_err(|_| "malformed edition")?);
+            }
+            "Dimensions" => {
+                let (width, height) = value.split_once('x').ok_or("malformed dimensions")?;
+                self.dimensions = Some((
+                    width.parse().map_err(|_| "malformed dimensions")?,
+                    height.parse().map_err(|_| "malformed dimensions")?,
+                ));
+            }
+            "Pixel hash" => {
+                let mut pixel_hash = [0; 32];
+                hex::decode_to_slice(value, &mut pixel_hash).map_err(|_| "malformed pixel hash")?;
+                self.pixel_hash = Some(pixel_hash);
+            }
+            "Color" => {
+                self.color_mode = Some(ColorMode::from_name(value).ok_or("unknown color mode")?);
+            }
+            "Algorithm" => {
+                self.signature_algorithm = Some(
+                    SignatureAlgorithm::from_name(value).ok_or("unknown signature algorithm")?,
+                );
+            }
+            _ => return Err("unrecognized metadata"),
+        }
+        Ok(())
+    }
+
+    pub fn finish(self) -> Result<Metadata, &'static str> {
+        Ok(Metadata {
+            subject: self.subject.ok_or("missing subject")?,
+            edition: self.edition.ok_or("missing edition")?,
+            dimensions: self.dimensions.ok_or("missing dimensions")?,
+            pixel_hash: self.pixel_hash.ok_or("missing pixel hash")?,
+            color_mode: self.color_mode.unwrap_or(ColorMode::Off),
+            signature_algorithm: self
+                .signature_algorithm
+                .ok_or("missing signature algorithm")?,
+        })
+    }
+}
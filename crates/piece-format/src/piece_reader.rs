@@ -0,0 +1,181 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::mem;
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    decode_signature_line, Art, Metadata, MetadataReader, MAX_METADATA_LINES, SIGNATURE_HEADER,
+};
+
+// Accumulates a piece, as pasted into a terminal, one line at a time. An empty line after the
+// signature ends the piece.
+//
+// Rows of the art may be empty once trailing spaces are stripped, so an empty line does not end the
+// art. Instead, the lines since the last empty one are held back until it is known whether they
+// are rows or metadata: they are metadata if "Signature:" follows them, and rows if another empty
+// line does, or if there are more of them than metadata has lines. The metadata gives the
+// masterpiece's dimensions, and so which rows are its own. Empty lines before the art are ignored.
+//
+// Besides the lines held back, which the caller bounds, a piece takes at most twice its size in
+// memory while it is read, and twice that while it is padded out to its dimensions.
+//
+// A piece found to be malformed part of the way through is still read to its end, so that none of
+// the rest of it is taken for commands.
+pub struct PieceReader {
+    max_size: usize,
+    section: Section,
+    art: Art,
+    // Whether there is an empty line before `held_back` that is not yet a row of `art`
+    after_empty_line: bool,
+    held_back: VecDeque<String>,
+    metadata: Option<Metadata>,
+    signature: Vec<u8>,
+    // The first error found, if any
+    error: Option<&'static str>,
+}
+
+pub struct Piece {
+    pub metadata: Metadata,
+    pub pixel_data: String,
+    pub colors: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Section {
+    ArtAndMetadata,
+    Signature,
+}
+
+impl PieceReader {
+    // Pieces whose pixel data, colours and signature together take more than `max_size` bytes are
+    // refused
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            max_size,
+            section: Section::ArtAndMetadata,
+            art: Art::new(),
+            after_empty_line: false,
+            held_back: VecDeque::new(),
+            metadata: None,
+            signature: Vec::new(),
+            error: None,
+        }
+    }
+
+    // Returns the piece, or the first error found in it, once it has ended
+    pub fn push_line(&mut self, line: &str) -> Result<Option<Piece>, &'static str> {
+        let err = match self.error {
+            Some(err) => err,
+            None => match self.read_line(line) {
+                Ok(piece) => return Ok(piece),
+                Err(err) => *self.error.insert(err),
+            },
+        };
+        if line == SIGNATURE_HEADER {
+            self.section = Section::Signature;
+        } else if line.is_empty() && self.section == Section::Signature {
+            return Err(err);
+        }
+        Ok(None)
+    }
+
+    // Marks the piece as malformed, for an error found outside of it, such as in how it was pasted
+    pub fn fail(&mut self, err: &'static str) {
+        self.error.get_or_insert(err);
+    }
+
+    fn read_line(&mut self, line: &str) -> Result<Option<Piece>, &'static str> {
+        match self.section {
+            Section::ArtAndMetadata if line == SIGNATURE_HEADER => {
+                if self.art.is_empty() {
+                    return Err("no masterpiece before signature");
+                }
+                if !self.after_empty_line {
+                    return Err("expected an empty line before metadata");
+                }
+                self.metadata = Some(self.take_metadata()?);
+                self.section = Section::Signature;
+            }
+            Section::ArtAndMetadata if line.is_empty() => {
+                while !self.held_back.is_empty() {
+                    self.release_row()?;
+                }
+                if mem::replace(&mut self.after_empty_line, true) {
+                    self.art.push_row("")?;
+                }
+            }
+            Section::ArtAndMetadata => {
+                self.held_back.push_back(line.into());
+                if self.held_back.len() > MAX_METADATA_LINES {
+                    self.release_row()?;
+                }
+            }
+            Section::Signature => {
+                if line.is_empty() {
+                    return self.take_piece().map(Some);
+                }
+                decode_signature_line(line, &mut self.signature)?;
+            }
+        }
+        if self.art.size() + self.signature.len() > self.max_size {
+            return Err("masterpiece is too large");
+        }
+        Ok(None)
+    }
+
+    // Takes the first line held back as a row, after the empty line before it, if any
+    fn release_row(&mut self) -> Result<(), &'static str> {
+        if mem::replace(&mut self.after_empty_line, false) {
+            self.art.push_row("")?;
+        }
+        let row = self.held_back.pop_front().unwrap();
+        self.art.push_row(&row)
+    }
+
+    fn take_metadata(&mut self) -> Result<Metadata, &'static str> {
+        let mut metadata = MetadataReader::new();
+        for line in self.held_back.drain(..) {
+            metadata.push_line(&line)?;
+        }
+        metadata.finish()
+    }
+
+    fn take_piece(&mut self) -> Result<Piece, &'static str> {
+        if self.signature.is_empty() {
+            return Err("missing signature");
+        }
+        let metadata = self.metadata.take().unwrap();
+        // Every pixel takes a byte and its colours, which must fit before the rows are padded
+        let (width, height) = metadata.dimensions;
+        let min_size = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(1 + metadata.color_mode.cell_size()))
+            .and_then(|size| size.checked_add(self.signature.len()));
+        if !matches!(min_size, Some(min_size) if min_size <= self.max_size) {
+            return Err("masterpiece is too large");
+        }
+        let (pixel_data, colors) =
+            mem::take(&mut self.art).finish(metadata.dimensions, metadata.color_mode)?;
+        if pixel_data.len() + colors.len() + self.signature.len() > self.max_size {
+            return Err("masterpiece is too large");
+        }
+        if metadata.pixel_hash[..] != Sha256::digest(pixel_data.as_bytes())[..] {
+            return Err("pixel hash does not match masterpiece");
+        }
+        Ok(Piece {
+            metadata,
+            pixel_data,
+            colors,
+            signature: mem::take(&mut self.signature),
+        })
+    }
+}
@@ -0,0 +1,192 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+use sha2::{Digest, Sha256};
+
+use banscii_artist_interface_types::ColorMode;
+use banscii_piece_format::{Piece, PieceReader};
+
+// Blank rows and pixels at the ends of rows are all spaces
+const QUADRANTS: &[&str] = &["      ", " ▗▄▖  ", " ▐█▌  ", "      ", " ▝▀▘ ▖", "      "];
+
+const INVERTED: &[&str] = &["        ", "  .:=:. ", " :#@@#: ", "  .:=:. ", "        "];
+
+// Prints a piece as `Assistant::create` does, with colours for each row if given
+fn print(art: &[&str], colors: Option<(ColorMode, &[&str])>) -> String {
+    let width = art[0].chars().count();
+    let mut out = String::from("\n");
+    match colors {
+        None => {
+            for row in art {
+                out += &format!("{row}\n");
+            }
+        }
+        Some((_, rows)) => {
+            for row in rows {
+                out += &format!("{row}\n");
+            }
+        }
+    }
+    out += "\nSubject: Test\nEdition: 7\n";
+    out += &format!("Dimensions: {}x{}\n", width, art.len());
+    out += &format!(
+        "Pixel hash: {}\n",
+        hex::encode(Sha256::digest(art.concat()))
+    );
+    if let Some((color_mode, _)) = colors {
+        out += &format!("Color: {}\n", color_mode.name());
+    }
+    out += "Algorithm: ed25519\nSignature:\n0123456789abcdef\nfedcba9876543210\n\n";
+    out
+}
+
+// As copied out of a terminal
+fn strip_trailing_spaces(text: &str) -> String {
+    text.lines()
+        .map(|line| line.trim_end_matches(' '))
+        .map(|line| format!("{line}\n"))
+        .collect()
+}
+
+fn paste(text: &str) -> Result<Piece, &'static str> {
+    let mut piece_reader = PieceReader::new(0x80_000);
+    for line in text.lines() {
+        if let Some(piece) = piece_reader.push_line(line)? {
+            return Ok(piece);
+        }
+    }
+    panic!("piece did not end");
+}
+
+fn check(piece: &Piece, art: &[&str]) {
+    assert_eq!(piece.pixel_data, art.concat());
+    assert_eq!(piece.metadata.subject, "Test");
+    assert_eq!(piece.metadata.edition, 7);
+    assert_eq!(
+        piece.metadata.dimensions,
+        (art[0].chars().count(), art.len())
+    );
+    assert_eq!(
+        piece.signature,
+        hex::decode("0123456789abcdeffedcba9876543210").unwrap()
+    );
+}
+
+#[test]
+fn exact() {
+    for art in [QUADRANTS, INVERTED] {
+        let piece = paste(&print(art, None)).unwrap();
+        check(&piece, art);
+        assert!(piece.colors.is_empty());
+    }
+}
+
+#[test]
+fn trailing_spaces_stripped() {
+    for art in [QUADRANTS, INVERTED] {
+        let text = strip_trailing_spaces(&print(art, None));
+        // Blank rows are now empty lines, like the one before the metadata
+        assert!(text.contains("\n\n\n"));
+        check(&paste(&text).unwrap(), art);
+    }
+}
+
+#[test]
+fn without_leading_empty_lines() {
+    // Only the empty line printed before the art, and not its first row, which is blank
+    let text = strip_trailing_spaces(&print(QUADRANTS, None));
+    check(&paste(text.strip_prefix('\n').unwrap()).unwrap(), QUADRANTS);
+}
+
+#[test]
+fn with_extra_leading_empty_lines() {
+    let text = strip_trailing_spaces(&print(INVERTED, None));
+    check(&paste(&format!("\n\n{text}")).unwrap(), INVERTED);
+}
+
+#[test]
+fn colors() {
+    let rows: &[&str] = &[
+        "\x1b[38;5;196;48;5;16m#\x1b[38;5;46;48;5;22m@\x1b[0m",
+        "\x1b[38;5;196;48;5;16m:\x1b[38;5;46;48;5;22m \x1b[0m",
+    ];
+    let art = &["#@", ": "];
+    let piece = paste(&print(art, Some((ColorMode::Ansi256, rows)))).unwrap();
+    check(&piece, art);
+    assert_eq!(piece.metadata.color_mode, ColorMode::Ansi256);
+    assert_eq!(piece.colors, [196, 16, 46, 22, 196, 16, 46, 22]);
+}
+
+#[test]
+fn wrong_color_mode() {
+    let rows: &[&str] = &["\x1b[38;5;196;48;5;16m#\x1b[0m"];
+    let text = print(&["#"], Some((ColorMode::TrueColor, rows)));
+    assert_eq!(
+        paste(&text).err(),
+        Some("masterpiece colours do not match its color mode")
+    );
+}
+
+#[test]
+fn row_too_wide() {
+    let text = print(INVERTED, None).replacen("  .:=:. ", "  .:=:.  ", 1);
+    assert_eq!(
+        paste(&text).err(),
+        Some("dimensions do not match masterpiece")
+    );
+}
+
+#[test]
+fn too_few_rows() {
+    let text = print(INVERTED, None).replacen(" :#@@#: \n", "", 1);
+    // The empty line printed before the art could otherwise pass for its first row
+    let text = text.strip_prefix('\n').unwrap();
+    assert_eq!(
+        paste(text).err(),
+        Some("dimensions do not match masterpiece")
+    );
+}
+
+#[test]
+fn dimensions_too_large() {
+    // Padding to these would take far more than the limit
+    let text = print(INVERTED, None).replacen("Dimensions: 8x5", "Dimensions: 100000000x5", 1);
+    assert_eq!(paste(&text).err(), Some("masterpiece is too large"));
+}
+
+#[test]
+fn lines_before_art() {
+    let text = format!("banscii> verify\n{}", print(INVERTED, None));
+    assert_eq!(
+        paste(&text).err(),
+        Some("dimensions do not match masterpiece")
+    );
+}
+
+#[test]
+fn tampered_pixels() {
+    let text = print(INVERTED, None).replacen(":#@@#:", ":#@%#:", 1);
+    assert_eq!(
+        paste(&text).err(),
+        Some("pixel hash does not match masterpiece")
+    );
+}
+
+#[test]
+fn malformed_piece_is_read_to_its_end() {
+    let text = print(INVERTED, None).replacen(":#@@#:", ":#\x1b[5m@@#:", 1);
+    let lines = text.lines().collect::<Vec<_>>();
+    let (last, rest) = lines.split_last().unwrap();
+    let mut piece_reader = PieceReader::new(0x80_000);
+    // Not even the metadata and signature are read once the piece is known to be bad
+    for line in rest {
+        assert!(matches!(piece_reader.push_line(line), Ok(None)), "{line:?}");
+    }
+    assert_eq!(
+        piece_reader.push_line(last).err(),
+        Some("unsupported escape sequence in masterpiece")
+    );
+}
@@ -0,0 +1,75 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+use std::cell::RefCell;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use banscii_artist_core::{Storage, StorageError};
+
+// Stands in for the flash that holds the artist's persistent state. Like NOR flash, erasing sets
+// every bit, and programming can only clear bits. Bytes beyond the end of the backing file read as
+// erased.
+pub(crate) struct IoStorage<F> {
+    // `Storage::read` takes `&self`, but seeking does not
+    inner: RefCell<F>,
+    sector_size: usize,
+}
+
+impl<F: Read + Write + Seek> IoStorage<F> {
+    pub(crate) fn new(inner: F, sector_size: usize) -> Self {
+        Self {
+            inner: RefCell::new(inner),
+            sector_size,
+        }
+    }
+
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> io::Result<()> {
+        let mut inner = self.inner.borrow_mut();
+        buf.fill(0xff);
+        inner.seek(SeekFrom::Start(offset.try_into().unwrap()))?;
+        let mut filled = 0;
+        while filled < buf.len() {
+            match inner.read(&mut buf[filled..])? {
+                0 => break,
+                n => filled += n,
+            }
+        }
+        Ok(())
+    }
+
+    fn write_at(&mut self, offset: usize, data: &[u8]) -> io::Result<()> {
+        let inner = self.inner.get_mut();
+        inner.seek(SeekFrom::Start(offset.try_into().unwrap()))?;
+        inner.write_all(data)?;
+        inner.flush()
+    }
+}
+
+impl<F: Read + Write + Seek> Storage for IoStorage<F> {
+    fn sector_size(&self) -> usize {
+        self.sector_size
+    }
+
+    // A failed read must not be mistaken for erased storage, which the artist would take to hold no
+    // records, and so restart editions from 1
+    fn read(&self, offset: usize, buf: &mut [u8]) -> Result<(), StorageError> {
+        self.read_at(offset, buf).map_err(|_| StorageError)
+    }
+
+    fn erase_sector(&mut self, sector: usize) -> Result<(), StorageError> {
+        self.write_at(sector * self.sector_size, &vec![0xff; self.sector_size])
+            .map_err(|_| StorageError)
+    }
+
+    fn program(&mut self, offset: usize, data: &[u8]) -> Result<(), StorageError> {
+        let mut bytes = vec![0; data.len()];
+        self.read_at(offset, &mut bytes).map_err(|_| StorageError)?;
+        for (b, d) in bytes.iter_mut().zip(data) {
+            *b &= d;
+        }
+        self.write_at(offset, &bytes).map_err(|_| StorageError)
+    }
+}
@@ -0,0 +1,159 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+// Runs the assistant and artist in a single host process. The channel between them becomes a
+// function call, the regions they share become plain buffers, and the terminal stands in for the
+// serial driver.
+
+use std::cell::RefCell;
+use std::env;
+use std::fs::OpenOptions;
+use std::io::{self, Cursor, Read, Write};
+use std::process::ExitCode;
+use std::rc::Rc;
+use std::time::Instant;
+
+use banscii_artist_core::{Artist, Storage};
+use banscii_artist_interface_types as artist;
+use banscii_assistant_core::{ArtistChannel, Assistant, RegionIn, RegionOut};
+
+mod io_storage;
+mod terminal;
+
+use io_storage::IoStorage;
+use terminal::Terminal;
+
+const REGION_SIZE: usize = 0x4_000;
+
+const PERSISTENT_STATE_SECTOR_SIZE: usize = 0x1_000;
+
+const USAGE: &str = "usage: banscii-sim [<persistent-state-file>]";
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let result = match args.as_slice() {
+        [] => run(IoStorage::new(
+            Cursor::new(Vec::new()),
+            PERSISTENT_STATE_SECTOR_SIZE,
+        )),
+        [path] => OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(|err| format!("failed to open {path}: {err}"))
+            .and_then(|file| run(IoStorage::new(file, PERSISTENT_STATE_SECTOR_SIZE))),
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(storage: impl Storage) -> Result<(), String> {
+    let artist = Artist::new(storage).map_err(|err| err.to_string())?;
+
+    let assistant_to_artist = Region::new();
+    let artist_to_assistant = Region::new();
+
+    let mut assistant = Assistant::new(
+        Terminal::new(),
+        SimArtist {
+            artist,
+            region_in: assistant_to_artist.clone(),
+            region_out: artist_to_assistant.clone(),
+            start: Instant::now(),
+        },
+        artist_to_assistant,
+        assistant_to_artist,
+    );
+
+    let _raw_mode = terminal::RawMode::enter();
+
+    assistant.start();
+    io::stdout().flush().unwrap();
+
+    let mut buf = [0; 256];
+    loop {
+        let n = io::stdin()
+            .read(&mut buf)
+            .map_err(|err| format!("failed to read stdin: {err}"))?;
+        let input = &buf[..n];
+        // End of input, ^C, or ^D
+        let end = input.iter().position(|b| matches!(b, 0x03 | 0x04));
+        assistant
+            .serial_mut()
+            .push_input(&input[..end.unwrap_or(n)]);
+        assistant.handle_serial_input();
+        io::stdout().flush().unwrap();
+        if n == 0 || end.is_some() {
+            break;
+        }
+    }
+
+    println!();
+
+    Ok(())
+}
+
+struct SimArtist<S> {
+    artist: Artist<S>,
+    region_in: Region,
+    region_out: Region,
+    start: Instant,
+}
+
+impl<S: Storage> ArtistChannel for SimArtist<S> {
+    fn call(&mut self, req: artist::Request) -> Result<artist::Response, artist::ArtistError> {
+        // Pass the request and response through their wire encoding, as the real channel would
+        let req = postcard::from_bytes(&postcard::to_allocvec(&req).unwrap()).unwrap();
+        let resp = self.artist.handle_request(
+            req,
+            self.start.elapsed().as_millis().try_into().unwrap(),
+            &self.region_in.0.borrow(),
+            &mut self.region_out.0.borrow_mut(),
+        );
+        postcard::from_bytes(&postcard::to_allocvec(&resp).unwrap()).unwrap()
+    }
+}
+
+#[derive(Clone)]
+struct Region(Rc<RefCell<Vec<u8>>>);
+
+impl Region {
+    fn new() -> Self {
+        Self(Rc::new(RefCell::new(vec![0; REGION_SIZE])))
+    }
+}
+
+impl RegionIn for Region {
+    fn size(&self) -> usize {
+        REGION_SIZE
+    }
+
+    fn read(&self, start: usize, buf: &mut [u8]) {
+        buf.copy_from_slice(&self.0.borrow()[start..][..buf.len()]);
+    }
+}
+
+impl RegionOut for Region {
+    fn size(&self) -> usize {
+        REGION_SIZE
+    }
+
+    fn write(&mut self, start: usize, data: &[u8]) {
+        self.0.borrow_mut()[start..][..data.len()].copy_from_slice(data);
+    }
+}
@@ -0,0 +1,94 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+use std::collections::VecDeque;
+use std::io::{self, Write as _};
+use std::mem::MaybeUninit;
+
+use embedded_hal_nb::nb;
+use embedded_hal_nb::serial::{self, ErrorKind, ErrorType};
+
+// Stands in for the serial driver. Input is pushed in by the caller, and output goes to stdout.
+pub(crate) struct Terminal {
+    input: VecDeque<u8>,
+}
+
+impl Terminal {
+    pub(crate) fn new() -> Self {
+        Self {
+            input: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn push_input(&mut self, bytes: &[u8]) {
+        self.input.extend(bytes);
+    }
+}
+
+impl ErrorType for Terminal {
+    type Error = ErrorKind;
+}
+
+impl serial::Read for Terminal {
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        self.input.pop_front().ok_or(nb::Error::WouldBlock)
+    }
+}
+
+impl serial::Write for Terminal {
+    fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        io::stdout()
+            .write_all(&[word])
+            .map_err(|_| nb::Error::Other(ErrorKind::Other))
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        io::stdout()
+            .flush()
+            .map_err(|_| nb::Error::Other(ErrorKind::Other))
+    }
+}
+
+// The assistant echoes input and handles line endings itself, like a program on the other end of
+// a serial line, so the terminal's own line discipline is switched off while the simulation runs.
+// Signals are also switched off, so that the terminal is always restored on the way out.
+pub(crate) struct RawMode {
+    original: Option<libc::termios>,
+}
+
+impl RawMode {
+    pub(crate) fn enter() -> Self {
+        let original = unsafe {
+            let mut termios = MaybeUninit::uninit();
+            if libc::isatty(libc::STDIN_FILENO) == 0
+                || libc::tcgetattr(libc::STDIN_FILENO, termios.as_mut_ptr()) != 0
+            {
+                None
+            } else {
+                Some(termios.assume_init())
+            }
+        };
+        if let Some(original) = original {
+            let mut raw = original;
+            raw.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG);
+            raw.c_iflag &= !libc::ICRNL;
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw);
+            }
+        }
+        Self { original }
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        if let Some(original) = &self.original {
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, original);
+            }
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::process::ExitCode;
+
+use sha2::{Digest, Sha256};
+
+use banscii_artist_interface_types::Provenance;
+
+mod public_key;
+mod transcript;
+
+use public_key::PublicKey;
+use transcript::Piece;
+
+const USAGE: &str = "usage: banscii-verify <public-key.pem> [<transcript>]";
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let (pub_key_path, transcript_path) = match args.as_slice() {
+        [pub_key_path] => (pub_key_path, None),
+        [pub_key_path, transcript_path] => (pub_key_path, Some(transcript_path)),
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(pub_key_path, transcript_path.map(String::as_str)) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(pub_key_path: &str, transcript_path: Option<&str>) -> Result<bool, String> {
+    let pub_key_pem = fs::read_to_string(pub_key_path)
+        .map_err(|err| format!("failed to read {pub_key_path}: {err}"))?;
+    let pub_key =
+        PublicKey::from_pem(&pub_key_pem).map_err(|err| format!("{pub_key_path}: {err}"))?;
+
+    let transcript = match transcript_path {
+        Some(path) => {
+            fs::read_to_string(path).map_err(|err| format!("failed to read {path}: {err}"))?
+        }
+        None => {
+            let mut buf = String::new();
+            io::stdin()
+                .read_to_string(&mut buf)
+                .map_err(|err| format!("failed to read stdin: {err}"))?;
+            buf
+        }
+    };
+
+    let pieces = transcript::parse(&transcript)?;
+    if pieces.is_empty() {
+        return Err("no masterpieces found in transcript".to_owned());
+    }
+
+    println!("Algorithm: {}", pub_key.signature_algorithm().name());
+    println!("Public key fingerprint (SHA-256):");
+    println!("{}", hex::encode(pub_key.fingerprint()));
+
+    let mut all_valid = true;
+    for piece in &pieces {
+        let verdict = verify(&pub_key, piece);
+        all_valid &= verdict.is_ok();
+        println!(
+            "line {}: {}x{} masterpiece, edition {}: {}",
+            piece.line_number,
+            piece.metadata.dimensions.0,
+            piece.metadata.dimensions.1,
+            piece.metadata.edition,
+            match verdict {
+                Ok(()) => "valid".to_owned(),
+                Err(reason) => format!("NOT valid ({reason})"),
+            },
+        );
+    }
+    Ok(all_valid)
+}
+
+fn verify(pub_key: &PublicKey, piece: &Piece) -> Result<(), String> {
+    let metadata = &piece.metadata;
+    let pixel_hash: [u8; 32] = Sha256::digest(&piece.pixel_data).into();
+    if metadata.pixel_hash != pixel_hash {
+        return Err("pixel hash does not match masterpiece".to_owned());
+    }
+    if metadata.signature_algorithm != pub_key.signature_algorithm() {
+        return Err(format!(
+            "signed using {}",
+            metadata.signature_algorithm.name()
+        ));
+    }
+    let provenance = Provenance {
+        height: metadata.dimensions.1,
+        width: metadata.dimensions.0,
+        subject: &metadata.subject,
+        edition: metadata.edition,
+        pixel_hash,
+        color_mode: metadata.color_mode,
+        color_hash: Sha256::digest(&piece.colors).into(),
+    };
+    let signed_data = postcard::to_allocvec(&provenance).unwrap();
+    if !pub_key.verify(&signed_data, &piece.signature) {
+        return Err("bad signature".to_owned());
+    }
+    Ok(())
+}
@@ -0,0 +1,77 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+use rsa::sha2::{Digest, Sha256};
+use rsa::signature::Verifier;
+
+use banscii_artist_interface_types::SignatureAlgorithm;
+
+// Mirrors the schemes in crates/artist/core/src/cryptographic_secrets/
+pub enum PublicKey {
+    RsaPkcs1v15Sha256(rsa::pkcs1v15::VerifyingKey<Sha256>),
+    Ed25519(ed25519_dalek::VerifyingKey),
+    EcdsaP256Sha256(p256::ecdsa::VerifyingKey),
+}
+
+impl PublicKey {
+    // Accepts SubjectPublicKeyInfo PEM, or PKCS#1 PEM for RSA
+    pub fn from_pem(pem: &str) -> Result<Self, String> {
+        use ed25519_dalek::pkcs8::DecodePublicKey as _;
+        use rsa::pkcs1::DecodeRsaPublicKey as _;
+        use rsa::pkcs8::DecodePublicKey as _;
+
+        if let Ok(key) = rsa::RsaPublicKey::from_public_key_pem(pem)
+            .or_else(|_| rsa::RsaPublicKey::from_pkcs1_pem(pem))
+        {
+            return Ok(Self::RsaPkcs1v15Sha256(
+                rsa::pkcs1v15::VerifyingKey::new_with_prefix(key),
+            ));
+        }
+        if let Ok(key) = ed25519_dalek::VerifyingKey::from_public_key_pem(pem) {
+            return Ok(Self::Ed25519(key));
+        }
+        if let Ok(key) = p256::ecdsa::VerifyingKey::from_public_key_pem(pem) {
+            return Ok(Self::EcdsaP256Sha256(key));
+        }
+        Err("unsupported or malformed public key".to_owned())
+    }
+
+    pub fn signature_algorithm(&self) -> SignatureAlgorithm {
+        match self {
+            Self::RsaPkcs1v15Sha256(_) => SignatureAlgorithm::RsaPkcs1v15Sha256,
+            Self::Ed25519(_) => SignatureAlgorithm::Ed25519,
+            Self::EcdsaP256Sha256(_) => SignatureAlgorithm::EcdsaP256Sha256,
+        }
+    }
+
+    // SHA-256 of the DER-encoded SubjectPublicKeyInfo, as printed by the assistant's `pubkey`
+    // command
+    pub fn fingerprint(&self) -> [u8; 32] {
+        use ed25519_dalek::pkcs8::EncodePublicKey as _;
+        use rsa::pkcs8::EncodePublicKey as _;
+
+        let der = match self {
+            Self::RsaPkcs1v15Sha256(key) => key.as_ref().to_public_key_der().unwrap().into_vec(),
+            Self::Ed25519(key) => key.to_public_key_der().unwrap().into_vec(),
+            Self::EcdsaP256Sha256(key) => key.to_public_key_der().unwrap().into_vec(),
+        };
+        Sha256::digest(der).into()
+    }
+
+    pub fn verify(&self, data: &[u8], signature: &[u8]) -> bool {
+        match self {
+            Self::RsaPkcs1v15Sha256(key) => rsa::pkcs1v15::Signature::try_from(signature)
+                .map(|signature| key.verify(data, &signature).is_ok())
+                .unwrap_or(false),
+            Self::Ed25519(key) => ed25519_dalek::Signature::from_slice(signature)
+                .map(|signature| key.verify(data, &signature).is_ok())
+                .unwrap_or(false),
+            Self::EcdsaP256Sha256(key) => p256::ecdsa::Signature::from_slice(signature)
+                .map(|signature| key.verify(data, &signature).is_ok())
+                .unwrap_or(false),
+        }
+    }
+}
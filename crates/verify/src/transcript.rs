@@ -0,0 +1,98 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+use banscii_piece_format::{
+    decode_signature_line, Art, Metadata, MetadataReader, SIGNATURE_HEADER,
+};
+
+// Extracts masterpieces from a serial transcript, as described in `banscii_piece_format`. The
+// transcript may hold anything else besides, so pieces are found by their "Signature:" lines, and
+// the rest of each is found by working back from there.
+
+pub struct Piece {
+    pub line_number: usize,
+    pub pixel_data: String,
+    pub colors: Vec<u8>,
+    pub metadata: Metadata,
+    pub signature: Vec<u8>,
+}
+
+pub fn parse(transcript: &str) -> Result<Vec<Piece>, String> {
+    let lines = transcript.lines().collect::<Vec<_>>();
+    let mut pieces = vec![];
+    for (i, line) in lines.iter().enumerate() {
+        if *line != SIGNATURE_HEADER {
+            continue;
+        }
+        let line_number = i + 1;
+
+        let metadata_start = preceding_block_start(&lines, i);
+        if metadata_start == 0 {
+            return Err(format!(
+                "line {line_number}: expected an empty line before metadata"
+            ));
+        }
+
+        let metadata = parse_metadata(&lines[metadata_start..i])
+            .map_err(|(j, err)| format!("line {}: {err}", metadata_start + j + 1))?;
+
+        // Art rows may be empty once trailing spaces are stripped, so the metadata says how many
+        // there are
+        let rows_end = metadata_start - 1;
+        let rows_start = rows_end
+            .checked_sub(metadata.dimensions.1)
+            .ok_or_else(|| format!("line {line_number}: too few rows before metadata"))?;
+
+        let mut art = Art::new();
+        for (j, row) in lines[rows_start..rows_end].iter().enumerate() {
+            art.push_row(row)
+                .map_err(|err| format!("line {}: {err}", rows_start + j + 1))?;
+        }
+        let (pixel_data, colors) = art
+            .finish(metadata.dimensions, metadata.color_mode)
+            .map_err(|err| format!("line {}: {err}", rows_start + 1))?;
+
+        let mut signature = vec![];
+        for (j, hex_line) in lines[i + 1..]
+            .iter()
+            .take_while(|hex_line| !hex_line.is_empty())
+            .enumerate()
+        {
+            decode_signature_line(hex_line, &mut signature)
+                .map_err(|err| format!("line {}: {err}", line_number + j + 1))?;
+        }
+        if signature.is_empty() {
+            return Err(format!("line {line_number}: missing signature"));
+        }
+
+        pieces.push(Piece {
+            line_number: rows_start + 1,
+            pixel_data,
+            colors,
+            metadata,
+            signature,
+        });
+    }
+    Ok(pieces)
+}
+
+// On error, returns the index of the offending line
+fn parse_metadata(lines: &[&str]) -> Result<Metadata, (usize, &'static str)> {
+    let mut metadata = MetadataReader::new();
+    for (j, line) in lines.iter().enumerate() {
+        metadata.push_line(line).map_err(|err| (j, err))?;
+    }
+    metadata.finish().map_err(|err| (lines.len(), err))
+}
+
+// Index of the first line of the run of non-empty lines ending just before `end`
+fn preceding_block_start(lines: &[&str], end: usize) -> usize {
+    lines[..end]
+        .iter()
+        .rposition(|line| line.is_empty())
+        .map(|j| j + 1)
+        .unwrap_or(0)
+}
@@ -0,0 +1,191 @@
+//
+// Copyright 2024, Colias Group, LLC
+//
+// SPDX-License-Identifier: BSD-2-Clause
+//
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use sha2::{Digest, Sha256};
+
+// A transcript of the simulator, with an RSA key, holding edition 1 of "Hi" and edition 2 of "Ho"
+// without colour, and edition 3 of "Hi" in 256 colours
+const TRANSCRIPT: &str = include_str!("data/transcript.txt");
+
+const PUB_KEY_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/pub.pem");
+
+const FIRST_ROW: &str = "@#x@@@+:@@@@@";
+
+const FIRST_PIXEL_HASH: &str = "46d80b71aac334447cb102baedb9cc795600c104126660ad1b1c6c856df98ef3";
+
+// Runs the verifier on `transcript`, returning whether every piece is valid, and what it printed
+// about each, or what it printed on failing to parse the transcript
+fn verify(transcript: &str) -> Result<(bool, Vec<String>), String> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_banscii-verify"))
+        .arg(PUB_KEY_PATH)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(transcript.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    if output.status.code() == Some(2) {
+        return Err(String::from_utf8(output.stderr).unwrap());
+    }
+    let verdicts = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .filter(|line| line.starts_with("line "))
+        .map(str::to_owned)
+        .collect();
+    Ok((output.status.success(), verdicts))
+}
+
+// Replaces the first occurrence of `from`, which must be present
+fn tamper(transcript: &str, from: &str, to: &str) -> String {
+    assert!(transcript.contains(from), "{from:?}");
+    transcript.replacen(from, to, 1)
+}
+
+#[test]
+fn valid() {
+    assert_eq!(
+        verify(TRANSCRIPT),
+        Ok((
+            true,
+            vec![
+                "line 3: 13x13 masterpiece, edition 1: valid".to_owned(),
+                "line 34: 17x13 masterpiece, edition 2: valid".to_owned(),
+                "line 66: 13x13 masterpiece, edition 3: valid".to_owned(),
+            ]
+        ))
+    );
+}
+
+#[test]
+fn tampered_pixels() {
+    let transcript = tamper(TRANSCRIPT, FIRST_ROW, "@#x@@@+:@@@@#");
+    let (valid, verdicts) = verify(&transcript).unwrap();
+    assert!(!valid);
+    assert_eq!(
+        verdicts[0],
+        "line 3: 13x13 masterpiece, edition 1: NOT valid (pixel hash does not match masterpiece)"
+    );
+    assert!(verdicts[1].ends_with(": valid"));
+}
+
+#[test]
+fn tampered_pixels_with_matching_hash() {
+    let transcript = tamper(TRANSCRIPT, FIRST_ROW, "@#x@@@+:@@@@#");
+    let pixel_data = transcript.lines().skip(2).take(13).collect::<String>();
+    let pixel_hash = hex::encode(Sha256::digest(pixel_data));
+    let transcript = tamper(&transcript, FIRST_PIXEL_HASH, &pixel_hash);
+    let (valid, verdicts) = verify(&transcript).unwrap();
+    assert!(!valid);
+    assert!(verdicts[0].ends_with(": NOT valid (bad signature)"));
+}
+
+#[test]
+fn tampered_dimensions() {
+    // Rows are taken from the dimensions, and padded to them as if trailing spaces were stripped
+    for to in ["Dimensions: 13x12", "Dimensions: 14x13"] {
+        let transcript = tamper(TRANSCRIPT, "Dimensions: 13x13", to);
+        let (valid, verdicts) = verify(&transcript).unwrap();
+        assert!(!valid, "{to:?}");
+        assert!(
+            verdicts[0].ends_with(": NOT valid (pixel hash does not match masterpiece)"),
+            "{to:?}"
+        );
+    }
+}
+
+#[test]
+fn tampered_metadata() {
+    for (from, to) in [
+        ("Subject: Hi\nEdition: 1", "Subject: Ho\nEdition: 1"),
+        ("Edition: 1", "Edition: 3"),
+        ("Edition: 2", "Edition: 1"),
+    ] {
+        let transcript = tamper(TRANSCRIPT, from, to);
+        let (valid, verdicts) = verify(&transcript).unwrap();
+        assert!(!valid, "{to:?}");
+        assert!(
+            verdicts.iter().any(|verdict| verdict.contains("NOT valid")),
+            "{to:?}"
+        );
+    }
+}
+
+#[test]
+fn tampered_algorithm() {
+    let transcript = tamper(
+        TRANSCRIPT,
+        "Algorithm: rsa-pkcs1v15-sha256",
+        "Algorithm: ed25519",
+    );
+    let (valid, verdicts) = verify(&transcript).unwrap();
+    assert!(!valid);
+    assert!(verdicts[0].ends_with(": NOT valid (signed using ed25519)"));
+}
+
+#[test]
+fn tampered_signature() {
+    let transcript = tamper(TRANSCRIPT, "c939905be1", "c939905be0");
+    let (valid, verdicts) = verify(&transcript).unwrap();
+    assert!(!valid);
+    assert!(verdicts[0].ends_with(": NOT valid (bad signature)"));
+}
+
+#[test]
+fn tampered_colors() {
+    // The third piece's second pixel in its fifth row, from dark red to dark magenta
+    let transcript = tamper(TRANSCRIPT, "48;5;52m+", "48;5;53m+");
+    let (valid, verdicts) = verify(&transcript).unwrap();
+    assert!(!valid);
+    assert!(verdicts[1].ends_with(": valid"));
+    assert!(verdicts[2].ends_with(": NOT valid (bad signature)"));
+}
+
+#[test]
+fn tampered_color_mode() {
+    for to in ["", "Color: truecolor\n"] {
+        let transcript = tamper(TRANSCRIPT, "Color: 256\n", to);
+        assert_eq!(
+            verify(&transcript),
+            Err("error: line 66: masterpiece colours do not match its color mode\n".to_owned())
+        );
+    }
+}
+
+#[test]
+fn malformed() {
+    let transcript = tamper(TRANSCRIPT, FIRST_ROW, "@#x@@@+:@@@@@@");
+    assert_eq!(
+        verify(&transcript),
+        Err("error: line 3: dimensions do not match masterpiece\n".to_owned())
+    );
+}
+
+#[test]
+fn missing_metadata() {
+    let transcript = tamper(TRANSCRIPT, "Edition: 1\n", "");
+    assert_eq!(
+        verify(&transcript),
+        Err("error: line 21: missing edition\n".to_owned())
+    );
+}
+
+#[test]
+fn no_pieces() {
+    assert_eq!(
+        verify("banscii> "),
+        Err("error: no masterpieces found in transcript\n".to_owned())
+    );
+}
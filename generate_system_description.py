@@ -29,6 +29,8 @@ def mk_context(board):
     if board == 'qemu_virt_aarch64':
         context['serial_mmio_phys_addr'] = 0x9000000
         context['serial_irq'] = 33
+        # Second pflash bank
+        context['persistent_state_phys_addr'] = 0x4000000
     else:
         raise Exception('unsupported configuration')
 